@@ -4,7 +4,7 @@ extern crate encoding;
 use std::env;
 
 use encoding::all::UTF_8;
-use loirc::{connect, Code, Event, Prefix, ReconnectionSettings};
+use loirc::{connect, Code, Event, Prefix, ReconnectionSettings, ServerInfo};
 
 /// Say "peekaboo" in a channel on freenode and then quit.
 /// target/debug/examples/peekaboo "#mychannel"
@@ -16,40 +16,36 @@ fn main() {
     let (writer, reader) = connect("irc.freenode.net:6667",
                                    ReconnectionSettings::DoNotReconnect,
                                    UTF_8).unwrap();
-    writer.raw(format!("USER {} 8 * :{}\n", "peekaboo", "peekaboo"));
-    writer.raw(format!("NICK {}\n", "peekaboo"));
+    let _ = writer.raw(format!("USER {} 8 * :{}\n", "peekaboo", "peekaboo"));
+    let _ = writer.raw(format!("NICK {}\n", "peekaboo"));
+
+    // Keep track of the ISUPPORT tokens sent by the server.
+    let mut info = ServerInfo::new();
 
     // Receive events.
     for event in reader.iter() {
         println!("{:?}", event);
-        match event {
-            Event::Message(msg) => {
-                if msg.code == Code::RplWelcome {
-                    // join channel, no password
-                    writer.raw(format!("JOIN {}\n", channel));
-                }
-                // JOIN is sent when you join a channel.
-                if msg.code == Code::Join {
-                    // If there is a prefix...
-                    if let Some(prefix) = msg.prefix {
-                        match prefix {
-                            // And the prefix is a user...
-                            Prefix::User(user) => {
-                                // And that user's nick is peekaboo, we've joined the channel!
-                                if user.nickname == "peekaboo" {
-                                    writer.raw(format!("PRIVMSG {} :{}\n", channel, "peekaboo"));
-                                    // Note that if the reconnection settings said to reconnect,
-                                    // it would. Close would "really" stop it.
-                                    writer.raw(format!("QUIT :{}\n", "peekaboo"));
-                                    // writer.close();
-                                }
-                            }
-                            _ => {}
-                        }
+        info.feed(&event);
+        if let Event::Message(msg) = event {
+            if msg.code == Code::RplWelcome {
+                // join channel, no password
+                let _ = writer.raw(format!("JOIN {}\n", channel));
+            }
+            // JOIN is sent when you join a channel.
+            if msg.code == Code::Join {
+                // If there is a prefix and the prefix is a user...
+                if let Some(Prefix::User(user)) = msg.prefix {
+                    // And that user's nick is peekaboo, we've joined the channel!
+                    // Nicknames must be compared with the server's case mapping.
+                    if info.case_mapping().equals(&user.nickname, "peekaboo") {
+                        let _ = writer.raw(format!("PRIVMSG {} :{}\n", channel, "peekaboo"));
+                        // Note that if the reconnection settings said to reconnect,
+                        // it would. Close would "really" stop it.
+                        let _ = writer.raw(format!("QUIT :{}\n", "peekaboo"));
+                        // writer.close();
                     }
                 }
             }
-            _ => {}
         }
     }
 }
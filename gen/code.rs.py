@@ -7,6 +7,18 @@ def gen_header():
     print "use std::fmt;"
     print "use std::str;"
 
+def gen_kind():
+    print "/// Kind of a code."
+    print "#[derive(Clone, Copy, Debug, Eq, PartialEq)]"
+    print "pub enum CodeKind {"
+    print "    /// Command, such as `PRIVMSG`."
+    print "    Command,"
+    print "    /// Numeric reply."
+    print "    Reply,"
+    print "    /// Numeric error."
+    print "    Error,"
+    print "}"
+
 def gen_enum(codes):
     print "/// Representation of IRC commands, replies and errors."
     print "#[derive(Clone, Debug, Eq, PartialEq)]"
@@ -14,33 +26,101 @@ def gen_enum(codes):
     for code in codes:
         print "    /// " + code.code + ' = "' + code.value + '"'
         print "    " + code.format_code + ","
-    print "    /// Codes that are unknown end up in here."
+    print "    /// Numerics that are unknown end up in here."
+    print "    Numeric(u16),"
+    print "    /// Commands that are unknown end up in here."
     print "    Unknown(String),"
     print "}"
 
 def gen_methods(codes):
     print "impl Code {"
     print
+    print "    /// Create the code of a numeric."
+    print "    pub fn from_numeric(numeric: u16) -> Code {"
+    print "        match numeric {"
+    for code in codes:
+        if not code.value.isdigit(): continue
+        print "            " + str(int(code.value)) + " => Code::" + code.format_code + ","
+    print "            _ => Code::Numeric(numeric),"
+    print "        }"
+    print "    }"
+    print
+    print "    /// Get the number of a numeric reply or error."
+    print "    pub fn numeric(&self) -> Option<u16> {"
+    print "        match *self {"
+    for code in codes:
+        if not code.value.isdigit(): continue
+        print "            Code::" + code.format_code + " => Some(" + str(int(code.value)) + "),"
+    print "            Code::Numeric(numeric) => Some(numeric),"
+    print "            _ => None,"
+    print "        }"
+    print "    }"
+    print
+    print "    /// Get the kind of the code."
+    print "    ///"
+    print "    /// Unknown numerics from 400 to 599 are errors, the other ones are replies."
+    print "    pub fn kind(&self) -> CodeKind {"
+    print "        if self.is_error() {"
+    print "            CodeKind::Error"
+    print "        } else if self.is_reply() {"
+    print "            CodeKind::Reply"
+    print "        } else {"
+    print "            CodeKind::Command"
+    print "        }"
+    print "    }"
+    print
     print "    /// Checks if the code is a reply."
     print "    pub fn is_reply(&self) -> bool {"
     print "        match *self {"
-    for code in codes:
-        if not code.reply: continue
-        print "            Code::" + code.format_code + " => true,"
-    print "            _  => false,"
+    print "            Code::Numeric(numeric) => !(400..600).contains(&numeric),"
+    names = ["Code::" + code.format_code for code in codes if code.reply]
+    print "            " + " |\n            ".join(names) + " => true,"
+    print "            _ => false,"
     print "        }"
     print "    }"
     print
     print "    /// Check if the code is en error."
     print "    pub fn is_error(&self) -> bool {"
     print "        match *self {"
-    for code in codes:
-        if not code.error: continue
-        print "            Code::" + code.format_code + " => true,"
-    print "            _  => false,"
+    print "            Code::Numeric(numeric) => (400..600).contains(&numeric),"
+    names = ["Code::" + code.format_code for code in codes if code.error]
+    print "            " + " |\n            ".join(names) + " => true,"
+    print "            _ => false,"
     print "        }"
     print "    }"
     print
+    print "    /// Get the name given by the specification, such as `ERR_NICKNAMEINUSE` or `PRIVMSG`."
+    print "    pub fn name(&self) -> Option<&'static str> {"
+    print "        let name = match *self {"
+    for code in codes:
+        print "            Code::" + code.format_code + ' => "' + code.code + '",'
+    print "            _ => return None,"
+    print "        };"
+    print "        Some(name)"
+    print "    }"
+    print
+    print "    /// Get a short description, such as `Nickname is already in use`."
+    print "    pub fn description(&self) -> Option<&'static str> {"
+    print "        let description = match *self {"
+    for code in codes:
+        print "            Code::" + code.format_code + ' => "' + code.description + '",'
+    print "            _ => return None,"
+    print "        };"
+    print "        Some(description)"
+    print "    }"
+    print
+    print "    /// Get the minimum amount of parameters of a valid message with this code."
+    print "    ///"
+    print "    /// For replies and errors, it includes the client's nickname."
+    print "    pub fn min_params(&self) -> Option<usize> {"
+    print "        let min_params = match *self {"
+    for code in codes:
+        print "            Code::" + code.format_code + " => " + str(code.min_params) + ","
+    print "            _ => return None,"
+    print "        };"
+    print "        Some(min_params)"
+    print "    }"
+    print
     print "}"
 
 def gen_display(codes):
@@ -50,6 +130,7 @@ def gen_display(codes):
     print "        let text = match *self {"
     for code in codes:
         print "            Code::" + code.format_code + " => " + code.format_value + ","
+    print "            Code::Numeric(numeric) => return write!(f, \"{:03}\", numeric),"
     print "            Code::Unknown(ref text) => &text[..],"
     print "        };"
     print "        f.write_str(text)"
@@ -61,20 +142,57 @@ def gen_fromstr(codes):
     print "impl str::FromStr for Code {"
     print "    type Err = ();"
     print
+    print "    /// Commands are case insensitive."
     print "    fn from_str(s: &str) -> Result<Code, ()> {"
-    print "        let code = match s {"
-    for code in codes:
-        print "            " + code.format_value + " => Code::" + code.format_code + ","
+    print "        if s.len() == 3 && s.bytes().all(|b| b.is_ascii_digit()) {"
+    print "            // Three digits always fit."
+    print "            return Ok(Code::from_numeric(s.parse().unwrap()));"
+    print "        }"
+    commands = [code for code in codes if not code.value.isdigit()]
+    print "        // Uppercase on the stack, longer commands are all unknown."
+    print "        let mut buf = [0u8; " + str(max(len(code.value) for code in commands)) + "];"
+    print "        if s.len() > buf.len() {"
+    print "            return Ok(Code::Unknown(s.to_string()));"
+    print "        }"
+    print "        let upper = &mut buf[..s.len()];"
+    print "        upper.copy_from_slice(s.as_bytes());"
+    print "        upper.make_ascii_uppercase();"
+    print "        let code = match &upper[..] {"
+    for code in commands:
+        print "            b" + code.format_value + " => Code::" + code.format_code + ","
     print "            _ => Code::Unknown(s.to_string()),"
     print "        };"
     print "        Ok(code)"
     print "    }"
     print "}"
 
+def gen_serde():
+    print "/// Serialized as its wire string, such as `PRIVMSG` or `001`."
+    print '#[cfg(feature = "serde")]'
+    print "impl ::serde::Serialize for Code {"
+    print
+    print "    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {"
+    print "        serializer.collect_str(self)"
+    print "    }"
+    print
+    print "}"
+    print
+    print '#[cfg(feature = "serde")]'
+    print "impl<'de> ::serde::Deserialize<'de> for Code {"
+    print
+    print "    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Code, D::Error> {"
+    print "        let text: String = ::serde::Deserialize::deserialize(deserializer)?;"
+    print "        text.parse().map_err(|()| ::serde::de::Error::custom(\"invalid code\"))"
+    print "    }"
+    print
+    print "}"
+
 if __name__ == '__main__':
     codes = parser.parse("codes.txt")
     gen_header()
     print
+    gen_kind()
+    print
     gen_enum(codes)
     print
     gen_methods(codes)
@@ -82,3 +200,5 @@ if __name__ == '__main__':
     gen_display(codes)
     print
     gen_fromstr(codes)
+    print
+    gen_serde()
@@ -3,9 +3,11 @@ def capfirst(s):
 
 class Code:
 
-    def __init__(self, code, value):
+    def __init__(self, code, value, min_params, description):
         self.code = code
         self.value = value
+        self.min_params = min_params
+        self.description = description
         self.reply = code.startswith("RPL_")
         self.error = code.startswith("ERR_")
         self.format_code = self._format_code()
@@ -22,13 +24,12 @@ class Code:
         return '"' + self.value.upper() + '"'
 
 
+# Each line is: name, value, minimum amount of parameters, description.
 def parse(path):
     f = open(path, "r")
     codes = []
-    while True:
-        code = f.readline()
-        if not code: break
-        value = f.readline()
-        if not value: break
-        codes.append(Code(code.strip(), value.strip()))
+    for line in f:
+        if not line.strip(): continue
+        code, value, min_params, description = line.split(None, 3)
+        codes.append(Code(code, value, int(min_params), description.strip()))
     return codes
@@ -61,7 +61,7 @@ impl State {
     }
 
     // Get the connection status.
-    fn connection_status<'a>(&self) -> MutexGuard<ConnectionStatus> {
+    fn connection_status(&self) -> MutexGuard<'_, ConnectionStatus> {
         self.status.lock().unwrap()
     }
 
@@ -71,7 +71,7 @@ impl State {
     }
 
     // Get the server's name.
-    fn get_server(&self) -> MutexGuard<Option<String>> {
+    fn get_server(&self) -> MutexGuard<'_, Option<String>> {
         self.server.lock().unwrap()
     }
 
@@ -209,7 +209,7 @@ impl ActivityMonitor {
         });
 
         ActivityMonitor {
-            state: state,
+            state,
         }
     }
 
@@ -231,14 +231,9 @@ impl ActivityMonitor {
             }
             Event::Message(ref msg) => {
                 self.state.set_activity(Instant::now());
-                if let Some(ref prefix) = msg.prefix {
-                    match *prefix {
-                        Prefix::Server(ref name) => {
-                            if !self.state.has_server() {
-                                self.state.set_server(name.clone());
-                            }
-                        }
-                        _ => {}
+                if let Some(Prefix::Server(ref name)) = msg.prefix {
+                    if !self.state.has_server() {
+                        self.state.set_server(name.clone());
                     }
                 }
             }
@@ -0,0 +1,182 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Computes the delay before the next reconnection attempt.
+pub trait BackoffPolicy: Send + Sync + 'static {
+
+    /// Get the delay to wait before the next attempt.
+    ///
+    /// `base` is `delay_between_attempts`, `failures` is the amount of consecutive
+    /// failures, 1 after the first one, and `previous` is the last delay, `base` at first.
+    fn delay(&self, base: Duration, failures: u32, previous: Duration) -> Duration;
+
+}
+
+/// How the delay between reconnection attempts grows.
+///
+/// When a network has an outage, every client reconnects at the same time.
+/// `DecorrelatedJitter` spreads the attempts of many clients, so that they are
+/// not throttled by the server's connection limits.
+#[derive(Clone)]
+pub enum Backoff {
+    /// Always wait `delay_between_attempts`.
+    Constant,
+    /// Wait `step` more after each failure, up to `max`.
+    Linear {
+        /// Increase of the delay after each failure.
+        step: Duration,
+        /// Longest delay.
+        max: Duration,
+    },
+    /// Double the delay after each failure, up to `max`.
+    Exponential {
+        /// Longest delay.
+        max: Duration,
+    },
+    /// Wait a random delay between `delay_between_attempts` and three times the previous delay, up to `max`.
+    DecorrelatedJitter {
+        /// Longest delay.
+        max: Duration,
+    },
+    /// Use a custom policy.
+    Custom(Arc<dyn BackoffPolicy>),
+}
+
+/// `Constant`
+impl Default for Backoff {
+
+    fn default() -> Backoff {
+        Backoff::Constant
+    }
+
+}
+
+impl BackoffPolicy for Backoff {
+
+    fn delay(&self, base: Duration, failures: u32, previous: Duration) -> Duration {
+        let steps = failures.saturating_sub(1);
+        match *self {
+            Backoff::Constant => base,
+            Backoff::Linear { step, max } => base.saturating_add(step.saturating_mul(steps)).min(max),
+            Backoff::Exponential { max } => {
+                let factor = 1u32.checked_shl(steps).unwrap_or(u32::MAX);
+                base.saturating_mul(factor).min(max)
+            }
+            Backoff::DecorrelatedJitter { max } => {
+                let high = previous.saturating_mul(3).as_nanos().min(u64::MAX as u128) as u64;
+                let low = base.as_nanos().min(high as u128) as u64;
+                let delay = low + random() % (high - low + 1);
+                Duration::from_nanos(delay).min(max)
+            }
+            Backoff::Custom(ref policy) => policy.delay(base, failures, previous),
+        }
+    }
+
+}
+
+impl fmt::Debug for Backoff {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Backoff::Constant => f.write_str("Constant"),
+            Backoff::Linear { step, max } => f.debug_struct("Linear").field("step", &step).field("max", &max).finish(),
+            Backoff::Exponential { max } => f.debug_struct("Exponential").field("max", &max).finish(),
+            Backoff::DecorrelatedJitter { max } => f.debug_struct("DecorrelatedJitter").field("max", &max).finish(),
+            Backoff::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+
+}
+
+/// Custom policies are equal if they are the same object.
+impl PartialEq for Backoff {
+
+    fn eq(&self, other: &Backoff) -> bool {
+        match (self, other) {
+            (Backoff::Constant, Backoff::Constant) => true,
+            (Backoff::Linear { step, max }, Backoff::Linear { step: other_step, max: other_max }) => {
+                step == other_step && max == other_max
+            }
+            (Backoff::Exponential { max }, Backoff::Exponential { max: other }) => max == other,
+            (Backoff::DecorrelatedJitter { max }, Backoff::DecorrelatedJitter { max: other }) => max == other,
+            (Backoff::Custom(policy), Backoff::Custom(other)) => {
+                Arc::as_ptr(policy) as *const () == Arc::as_ptr(other) as *const ()
+            }
+            _ => false,
+        }
+    }
+
+}
+
+impl Eq for Backoff {}
+
+// Random delay up to the given one.
+pub(crate) fn jitter(max: Duration) -> Duration {
+    let max = max.as_nanos().min(u64::MAX as u128 - 1) as u64;
+    Duration::from_nanos(random() % (max + 1))
+}
+
+// The keys of the standard hasher are random, different for every process and every call.
+fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    hasher.write_u128(now.as_nanos());
+    hasher.finish()
+}
+
+#[test]
+fn test_backoff() {
+    let base = Duration::from_secs(5);
+    let max = Duration::from_secs(60);
+    let delays = |backoff: Backoff| -> Vec<u64> {
+        (1..7).map(|failures| backoff.delay(base, failures, base).as_secs()).collect()
+    };
+    assert_eq!(delays(Backoff::Constant), vec![5, 5, 5, 5, 5, 5]);
+    assert_eq!(delays(Backoff::Linear { step: Duration::from_secs(10), max }), vec![5, 15, 25, 35, 45, 55]);
+    assert_eq!(delays(Backoff::Exponential { max }), vec![5, 10, 20, 40, 60, 60]);
+    assert_eq!(Backoff::Exponential { max }.delay(base, 200, base), max);
+}
+
+#[test]
+fn test_decorrelated_jitter() {
+    let base = Duration::from_secs(1);
+    let max = Duration::from_secs(30);
+    let backoff = Backoff::DecorrelatedJitter { max };
+    let mut previous = base;
+    let mut delays = Vec::new();
+    for failures in 1..50 {
+        let delay = backoff.delay(base, failures, previous);
+        assert!(delay >= base && delay <= max && delay <= previous * 3);
+        delays.push(delay);
+        previous = delay;
+    }
+    // Not in lockstep.
+    assert!(delays.windows(2).any(|pair| pair[0] != pair[1]));
+}
+
+#[test]
+fn test_custom() {
+    struct Fibonacci;
+
+    impl BackoffPolicy for Fibonacci {
+        fn delay(&self, base: Duration, failures: u32, _: Duration) -> Duration {
+            let (mut a, mut b) = (1, 1);
+            for _ in 1..failures {
+                let next = a + b;
+                a = b;
+                b = next;
+            }
+            base * a
+        }
+    }
+
+    let backoff = Backoff::Custom(Arc::new(Fibonacci));
+    let delays: Vec<u64> = (1..6).map(|failures| backoff.delay(Duration::from_secs(1), failures, Duration::ZERO).as_secs())
+        .collect();
+    assert_eq!(delays, vec![1, 1, 2, 3, 5]);
+    assert_eq!(backoff, backoff.clone());
+    assert!(backoff != Backoff::Custom(Arc::new(Fibonacci)));
+}
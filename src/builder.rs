@@ -0,0 +1,262 @@
+use std::error;
+use std::fmt;
+
+use code::Code;
+use message::{is_valid_tag_key, Message, Prefix, Tag, MAX_LINE_LENGTH, MAX_PARAMS, MAX_TAGS_LENGTH};
+
+/// Error generated by the `MessageBuilder`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BuildError {
+    /// Something contains a CR, LF or NUL character.
+    ForbiddenChar,
+    /// The prefix is empty, contains a space or starts with a colon.
+    InvalidPrefix,
+    /// The text of an unknown command is empty, contains a space or starts with a colon.
+    InvalidCommand,
+    /// A tag key is empty or contains characters that are not allowed.
+    InvalidTagKey,
+    /// The middle argument at this position is empty, contains a space or starts with a colon.
+    InvalidArg(usize),
+    /// An argument was added after the trailing argument.
+    ArgAfterTrailing,
+    /// The message has more than 15 arguments.
+    TooManyArgs,
+    /// The encoded line, without the tags, is longer than 512 bytes. The value is its length.
+    TooLong(usize),
+    /// The encoded tag section is too long, more than 8191 bytes for a message or
+    /// 4094 bytes of tags added by a `Splitter`. The value is its length.
+    TagsTooLong(usize),
+}
+
+impl fmt::Display for BuildError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::ForbiddenChar => f.write_str("forbidden CR, LF or NUL character"),
+            BuildError::InvalidPrefix => f.write_str("invalid prefix"),
+            BuildError::InvalidCommand => f.write_str("invalid command"),
+            BuildError::InvalidTagKey => f.write_str("invalid tag key"),
+            BuildError::InvalidArg(idx) => write!(f, "invalid argument at position {}", idx),
+            BuildError::ArgAfterTrailing => f.write_str("argument after the trailing argument"),
+            BuildError::TooManyArgs => f.write_str("too many arguments"),
+            BuildError::TooLong(len) => write!(f, "line is {} bytes long", len),
+            BuildError::TagsTooLong(len) => write!(f, "tags are {} bytes long", len),
+        }
+    }
+
+}
+
+impl error::Error for BuildError {}
+
+fn has_forbidden_char(text: &str) -> bool {
+    text.contains(['\r', '\n', '\0'])
+}
+
+/// Builds a `Message` which is guaranteed to be sendable.
+///
+/// Every part is validated as it is added. The first error is kept and
+/// returned by `build`.
+///
+/// ```
+/// use loirc::{Code, Message};
+///
+/// let msg = Message::builder(Code::Privmsg).arg("#chan").trailing("hello world").build().unwrap();
+/// assert_eq!(msg.to_line(), "PRIVMSG #chan :hello world\r\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct MessageBuilder {
+    msg: Message,
+    error: Option<BuildError>,
+}
+
+impl Message {
+
+    /// Start building a message with the given code.
+    pub fn builder(code: Code) -> MessageBuilder {
+        let error = match code {
+            Code::Unknown(ref text) if has_forbidden_char(text) => Some(BuildError::ForbiddenChar),
+            Code::Unknown(ref text) if text.is_empty() || text.contains(' ') || text.starts_with(':') => {
+                Some(BuildError::InvalidCommand)
+            }
+            _ => None,
+        };
+        MessageBuilder {
+            msg: Message {
+                tags: Vec::new(),
+                prefix: None,
+                code,
+                args: Vec::new(),
+                trailing: false,
+            },
+            error,
+        }
+    }
+
+}
+
+impl MessageBuilder {
+
+    // Keep the first error only, and tell if the part can be added.
+    fn accept(&mut self, res: Result<(), BuildError>) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        self.error = res.err();
+        self.error.is_none()
+    }
+
+    /// Set the prefix.
+    pub fn prefix(mut self, prefix: Prefix) -> MessageBuilder {
+        let text = prefix.to_string();
+        let res = if has_forbidden_char(&text) {
+            Err(BuildError::ForbiddenChar)
+        } else if text.is_empty() || text.contains(' ') || text.starts_with(':') {
+            Err(BuildError::InvalidPrefix)
+        } else {
+            Ok(())
+        };
+        if self.accept(res) {
+            self.msg.prefix = Some(prefix);
+        }
+        self
+    }
+
+    /// Add a tag, with an optional value.
+    ///
+    /// The value is escaped when the message is encoded.
+    pub fn tag(mut self, key: &str, value: Option<&str>) -> MessageBuilder {
+        let res = if has_forbidden_char(key) || value.is_some_and(|value| value.contains('\0')) {
+            Err(BuildError::ForbiddenChar)
+        } else if !is_valid_tag_key(key) {
+            Err(BuildError::InvalidTagKey)
+        } else {
+            Ok(())
+        };
+        if self.accept(res) {
+            self.msg.tags.push(Tag::new(key, value.map(|value| value.into())));
+        }
+        self
+    }
+
+    /// Add a middle argument.
+    ///
+    /// It cannot be empty, contain a space or start with a colon.
+    pub fn arg<S: Into<String>>(self, arg: S) -> MessageBuilder {
+        let arg = arg.into();
+        let res = if has_forbidden_char(&arg) {
+            Err(BuildError::ForbiddenChar)
+        } else if arg.is_empty() || arg.contains(' ') || arg.starts_with(':') {
+            Err(BuildError::InvalidArg(self.msg.args.len()))
+        } else {
+            Ok(())
+        };
+        self.push_arg(res, arg, false)
+    }
+
+    /// Add the trailing argument, which can contain spaces.
+    ///
+    /// No argument can be added after it.
+    pub fn trailing<S: Into<String>>(self, arg: S) -> MessageBuilder {
+        let arg = arg.into();
+        let res = if has_forbidden_char(&arg) {
+            Err(BuildError::ForbiddenChar)
+        } else {
+            Ok(())
+        };
+        self.push_arg(res, arg, true)
+    }
+
+    fn push_arg(mut self, res: Result<(), BuildError>, arg: String, trailing: bool) -> MessageBuilder {
+        let res = if self.msg.trailing {
+            Err(BuildError::ArgAfterTrailing)
+        } else if self.msg.args.len() >= MAX_PARAMS {
+            Err(BuildError::TooManyArgs)
+        } else {
+            res
+        };
+        if self.accept(res) {
+            self.msg.args.push(arg);
+            self.msg.trailing = trailing;
+        }
+        self
+    }
+
+    /// Finish building the message.
+    ///
+    /// The first error encountered is returned, or an error if the encoded
+    /// message is too long.
+    pub fn build(self) -> Result<Message, BuildError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        let line = self.msg.to_line();
+        let tags_len = if self.msg.tags.is_empty() {
+            0
+        } else {
+            line.find(' ').map_or(0, |idx| idx + 1)
+        };
+        if tags_len > MAX_TAGS_LENGTH {
+            return Err(BuildError::TagsTooLong(tags_len));
+        }
+        if line.len() - tags_len > MAX_LINE_LENGTH {
+            return Err(BuildError::TooLong(line.len() - tags_len));
+        }
+
+        Ok(self.msg)
+    }
+
+}
+
+#[test]
+fn test_build() {
+    let msg = Message::builder(Code::Privmsg)
+        .prefix(Prefix::parse("bob!bob@bob.com"))
+        .tag("msgid", Some("a b"))
+        .tag("+flag", None)
+        .arg("#chan")
+        .trailing("hello world")
+        .build()
+        .unwrap();
+    assert_eq!(msg.to_line(), "@msgid=a\\sb;+flag :bob!bob@bob.com PRIVMSG #chan :hello world\r\n");
+    assert_eq!(Message::parse(&msg.to_line()), Ok(msg));
+}
+
+#[test]
+fn test_invalid_args() {
+    assert_eq!(Message::builder(Code::Privmsg).arg("#chan").arg("a b").build(), Err(BuildError::InvalidArg(1)));
+    assert_eq!(Message::builder(Code::Privmsg).arg(":x").build(), Err(BuildError::InvalidArg(0)));
+    assert_eq!(Message::builder(Code::Privmsg).arg("").build(), Err(BuildError::InvalidArg(0)));
+    assert_eq!(Message::builder(Code::Privmsg).trailing("a").arg("b").build(), Err(BuildError::ArgAfterTrailing));
+    assert_eq!(Message::builder(Code::Privmsg).arg("#chan").trailing("a\r\nQUIT").build(), Err(BuildError::ForbiddenChar));
+    assert_eq!(Message::builder(Code::Privmsg).tag("a b", None).build(), Err(BuildError::InvalidTagKey));
+}
+
+#[test]
+fn test_invalid_command_and_prefix() {
+    let unknown = |text: &str| Message::builder(Code::Unknown(text.into())).arg("x").build();
+    assert_eq!(unknown(""), Err(BuildError::InvalidCommand));
+    assert_eq!(unknown("FOO BAR"), Err(BuildError::InvalidCommand));
+    assert_eq!(unknown(":FOO"), Err(BuildError::InvalidCommand));
+    assert_eq!(unknown("FOO\r\nQUIT"), Err(BuildError::ForbiddenChar));
+    assert!(unknown("FOO").is_ok());
+    let res = Message::builder(Code::Privmsg).prefix(Prefix::Server(":bob".into())).build();
+    assert_eq!(res, Err(BuildError::InvalidPrefix));
+}
+
+#[test]
+fn test_first_error_kept() {
+    let res = Message::builder(Code::Privmsg).arg("a b").trailing("x\0").build();
+    assert_eq!(res, Err(BuildError::InvalidArg(0)));
+}
+
+#[test]
+fn test_too_long() {
+    let text = "a".repeat(500);
+    assert_eq!(Message::builder(Code::Privmsg).arg("#chan").trailing(text).build(), Err(BuildError::TooLong(517)));
+    let mut builder = Message::builder(Code::Mode);
+    for _ in 0..16 {
+        builder = builder.arg("x");
+    }
+    assert_eq!(builder.build(), Err(BuildError::TooManyArgs));
+}
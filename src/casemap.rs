@@ -0,0 +1,230 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Rule used by a server to compare nicknames and channel names.
+///
+/// It is advertised by the `CASEMAPPING` ISUPPORT token. When the server does
+/// not advertise one, `rfc1459` must be assumed, which is the default.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CaseMapping {
+    /// `ascii`, only `A-Z` map to `a-z`.
+    Ascii,
+    /// `rfc1459`, like `ascii` and `[]\~` map to `{}|^`.
+    #[default]
+    Rfc1459,
+    /// `strict-rfc1459`, like `ascii` and `[]\` map to `{}|`.
+    StrictRfc1459,
+    /// `rfc7613`, Unicode case folding.
+    ///
+    /// This uses the Unicode lowercase mapping of each character, and does not apply the
+    /// NFKC normalization. Characters which lowercase to several characters are kept.
+    Rfc7613,
+}
+
+impl CaseMapping {
+
+    /// Get the case mapping from the value of the `CASEMAPPING` token.
+    pub fn from_token(token: &str) -> Option<CaseMapping> {
+        match token {
+            "ascii" => Some(CaseMapping::Ascii),
+            "rfc1459" => Some(CaseMapping::Rfc1459),
+            "strict-rfc1459" => Some(CaseMapping::StrictRfc1459),
+            "rfc7613" => Some(CaseMapping::Rfc7613),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the `CASEMAPPING` token for this case mapping.
+    pub fn token(&self) -> &'static str {
+        match *self {
+            CaseMapping::Ascii => "ascii",
+            CaseMapping::Rfc1459 => "rfc1459",
+            CaseMapping::StrictRfc1459 => "strict-rfc1459",
+            CaseMapping::Rfc7613 => "rfc7613",
+        }
+    }
+
+    /// Lowercase a single character.
+    pub fn to_lower_char(&self, c: char) -> char {
+        match (*self, c) {
+            (_, 'A'..='Z') => c.to_ascii_lowercase(),
+            (CaseMapping::Rfc1459, '[') | (CaseMapping::StrictRfc1459, '[') => '{',
+            (CaseMapping::Rfc1459, ']') | (CaseMapping::StrictRfc1459, ']') => '}',
+            (CaseMapping::Rfc1459, '\\') | (CaseMapping::StrictRfc1459, '\\') => '|',
+            (CaseMapping::Rfc1459, '~') => '^',
+            (CaseMapping::Rfc7613, _) => {
+                let mut lower = c.to_lowercase();
+                match (lower.next(), lower.next()) {
+                    (Some(l), None) => l,
+                    _ => c,
+                }
+            }
+            _ => c,
+        }
+    }
+
+    /// Normalize the text, so that equal names have the same normalized form.
+    pub fn normalize(&self, text: &str) -> String {
+        self.chars(text).collect()
+    }
+
+    fn chars<'a>(&self, text: &'a str) -> impl Iterator<Item = char> + 'a {
+        let mapping = *self;
+        text.chars().map(move |c| mapping.to_lower_char(c))
+    }
+
+    /// Check if two names are equal.
+    pub fn equals(&self, a: &str, b: &str) -> bool {
+        self.chars(a).eq(self.chars(b))
+    }
+
+    /// Compare two names.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.chars(a).cmp(self.chars(b))
+    }
+
+    /// Hash a name, so that equal names have the same hash.
+    pub fn hash_name<H: Hasher>(&self, text: &str, state: &mut H) {
+        for c in self.chars(text) {
+            c.hash(state);
+        }
+        // Same terminator as str, so that sequences of names hash properly.
+        0xffu8.hash(state);
+    }
+
+}
+
+/// Nickname or channel name which compares and hashes according to a case mapping.
+///
+/// It can be used as a `HashMap` key. Both keys must use the same case mapping
+/// for the comparison to make sense.
+#[derive(Clone, Debug)]
+pub struct IrcKey {
+    name: String,
+    normalized: String,
+    mapping: CaseMapping,
+}
+
+impl IrcKey {
+
+    /// Create a new key.
+    pub fn new<S: Into<String>>(name: S, mapping: CaseMapping) -> IrcKey {
+        let name = name.into();
+        IrcKey {
+            normalized: mapping.normalize(&name),
+            name,
+            mapping,
+        }
+    }
+
+    /// Get the name, as it was given.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the normalized name.
+    pub fn normalized(&self) -> &str {
+        &self.normalized
+    }
+
+    /// Get the case mapping.
+    pub fn mapping(&self) -> CaseMapping {
+        self.mapping
+    }
+
+}
+
+impl PartialEq for IrcKey {
+
+    fn eq(&self, other: &IrcKey) -> bool {
+        self.normalized == other.normalized
+    }
+
+}
+
+impl Eq for IrcKey {}
+
+impl Hash for IrcKey {
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
+    }
+
+}
+
+impl PartialOrd for IrcKey {
+
+    fn partial_cmp(&self, other: &IrcKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+
+}
+
+impl Ord for IrcKey {
+
+    fn cmp(&self, other: &IrcKey) -> Ordering {
+        self.normalized.cmp(&other.normalized)
+    }
+
+}
+
+impl fmt::Display for IrcKey {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+
+}
+
+#[test]
+fn test_equals() {
+    assert!(CaseMapping::Rfc1459.equals("Nick[away]~", "nick{AWAY}^"));
+    assert!(CaseMapping::StrictRfc1459.equals("Nick[away]", "nick{AWAY}"));
+    assert!(!CaseMapping::StrictRfc1459.equals("nick~", "nick^"));
+    assert!(!CaseMapping::Ascii.equals("nick[]", "nick{}"));
+    assert!(CaseMapping::Ascii.equals("NICK", "nick"));
+    assert!(CaseMapping::Rfc7613.equals("ÉTÉ", "été"));
+}
+
+#[test]
+fn test_rfc7613_final_sigma() {
+    // Each character is lowercased on its own, the final sigma is not special.
+    let mapping = CaseMapping::Rfc7613;
+    assert_eq!(mapping.normalize("ΟΔΥΣΣΕΥΣ"), "οδυσσευσ");
+    assert_eq!(mapping.to_lower_char('Σ'), 'σ');
+    assert!(mapping.equals("ΟΔΥΣΣΕΥΣ", "οδυσσευσ"));
+    assert!(!mapping.equals("ΟΔΥΣΣΕΥΣ", "οδυσσευς"));
+    assert_eq!(IrcKey::new("ΟΔΥΣΣΕΥΣ", mapping), IrcKey::new("οδυσσευσ", mapping));
+    assert_eq!(mapping.to_lower_char('İ'), 'İ');
+}
+
+#[test]
+fn test_from_token() {
+    for mapping in [CaseMapping::Ascii, CaseMapping::Rfc1459, CaseMapping::StrictRfc1459, CaseMapping::Rfc7613].iter() {
+        assert_eq!(CaseMapping::from_token(mapping.token()), Some(*mapping));
+    }
+    assert_eq!(CaseMapping::from_token("unknown"), None);
+}
+
+#[test]
+fn test_hash() {
+    use std::collections::hash_map::DefaultHasher;
+
+    let hash = |text: &str| {
+        let mut hasher = DefaultHasher::new();
+        CaseMapping::Rfc1459.hash_name(text, &mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(hash("Peek[a]boo"), hash("peek{A}BOO"));
+}
+
+#[test]
+fn test_key() {
+    use std::collections::HashMap;
+
+    let mut map = HashMap::new();
+    map.insert(IrcKey::new("#Chan[1]", CaseMapping::Rfc1459), 1);
+    assert_eq!(map.get(&IrcKey::new("#chan{1}", CaseMapping::Rfc1459)), Some(&1));
+    assert_eq!(map.keys().next().unwrap().as_str(), "#Chan[1]");
+}
@@ -0,0 +1,184 @@
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+use std::str;
+
+use encoding::{DecoderTrap, EncoderTrap, EncodingRef};
+use encoding::all::UTF_8;
+
+use message::{Message, ParseError};
+
+/// How lines received from the server are decoded.
+///
+/// UTF-8 is always tried first. Lines which are not valid UTF-8 are decoded
+/// with the fallback encoding, such as `WINDOWS_1252` or `ISO_8859_1`, if any.
+///
+/// The default has no fallback encoding and is not strict.
+#[derive(Clone, Copy, Default)]
+pub struct DecodePolicy {
+    /// Encoding used when the line is not valid UTF-8.
+    pub fallback: Option<EncodingRef>,
+    /// When `true`, lines that cannot be decoded are errors. Otherwise, invalid
+    /// sequences are replaced by U+FFFD.
+    pub strict: bool,
+}
+
+impl DecodePolicy {
+
+    /// Create a lenient policy falling back to the given encoding.
+    pub fn with_fallback(fallback: EncodingRef) -> DecodePolicy {
+        DecodePolicy {
+            fallback: Some(fallback),
+            strict: false,
+        }
+    }
+
+    /// Make the policy strict.
+    pub fn strict(mut self) -> DecodePolicy {
+        self.strict = true;
+        self
+    }
+
+    /// Decode the bytes, and tell how it was done.
+    ///
+    /// `None` is returned if the policy is strict and the bytes cannot be decoded.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Option<(Cow<'a, str>, Decoded)> {
+        if let Ok(text) = str::from_utf8(bytes) {
+            return Some((Cow::Borrowed(text), Decoded::Utf8));
+        }
+        if let Some(fallback) = self.fallback {
+            if let Ok(text) = fallback.decode(bytes, DecoderTrap::Strict) {
+                return Some((Cow::Owned(text), Decoded::Fallback(fallback.name())));
+            }
+        }
+        if self.strict {
+            return None;
+        }
+        // Replace with the fallback encoding's rules if there is one, it's closer to what was sent.
+        let encoding = self.fallback.unwrap_or(UTF_8);
+        encoding.decode(bytes, DecoderTrap::Replace).ok().map(|text| (Cow::Owned(text), Decoded::Lossy))
+    }
+
+}
+
+/// How a line was decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Decoded {
+    /// The line was valid UTF-8.
+    Utf8,
+    /// The line was decoded with the fallback encoding, whose name is given.
+    Fallback(&'static str),
+    /// The line could not be decoded properly and invalid sequences were replaced by U+FFFD.
+    Lossy,
+}
+
+/// How lines sent to the server are encoded.
+#[derive(Clone, Copy)]
+pub struct EncodePolicy {
+    /// Encoding of the lines.
+    pub encoding: EncodingRef,
+    /// What to do with characters that the encoding cannot represent.
+    ///
+    /// With `EncoderTrap::Strict`, the `Writer` returns `Error::Unencodable`.
+    pub trap: EncoderTrap,
+}
+
+/// UTF-8, which can encode everything.
+impl Default for EncodePolicy {
+
+    fn default() -> EncodePolicy {
+        EncodePolicy::new(UTF_8)
+    }
+
+}
+
+impl EncodePolicy {
+
+    /// Create a policy replacing the characters that cannot be encoded by `?`.
+    pub fn new(encoding: EncodingRef) -> EncodePolicy {
+        EncodePolicy {
+            encoding,
+            trap: EncoderTrap::Replace,
+        }
+    }
+
+    /// Encode the text, `None` is returned if the trap fails.
+    pub fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        self.encoding.encode(text, self.trap).ok()
+    }
+
+}
+
+/// Error generated by `Message::parse_bytes`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineError {
+    /// The line could not be decoded with a strict policy.
+    Decode,
+    /// The decoded line is not a valid message.
+    Parse(ParseError),
+}
+
+impl fmt::Display for LineError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LineError::Decode => f.write_str("line could not be decoded"),
+            LineError::Parse(ref err) => err.fmt(f),
+        }
+    }
+
+}
+
+impl error::Error for LineError {}
+
+impl Message {
+
+    /// Decode the bytes with the policy, and parse them into a `Message`.
+    ///
+    /// The way the line was decoded is returned with the message.
+    pub fn parse_bytes(bytes: &[u8], policy: &DecodePolicy) -> Result<(Message, Decoded), LineError> {
+        let (line, decoded) = policy.decode(bytes).ok_or(LineError::Decode)?;
+        Message::parse(&line).map(|msg| (msg, decoded)).map_err(LineError::Parse)
+    }
+
+}
+
+#[test]
+fn test_parse_bytes() {
+    use encoding::all::{ISO_8859_1, WINDOWS_1252};
+
+    let policy = DecodePolicy::with_fallback(WINDOWS_1252);
+    let (msg, decoded) = Message::parse_bytes("PRIVMSG #chan :été\r\n".as_bytes(), &policy).unwrap();
+    assert_eq!(msg.args[1], "été");
+    assert_eq!(decoded, Decoded::Utf8);
+
+    let (msg, decoded) = Message::parse_bytes(b"PRIVMSG #chan :\xe9t\xe9 \x80\r\n", &policy).unwrap();
+    assert_eq!(msg.args[1], "été €");
+    assert_eq!(decoded, Decoded::Fallback("windows-1252"));
+
+    let policy = DecodePolicy::with_fallback(ISO_8859_1);
+    let (msg, _) = Message::parse_bytes(b"PRIVMSG #chan :\xe9t\xe9\r\n", &policy).unwrap();
+    assert_eq!(msg.args[1], "été");
+}
+
+#[test]
+fn test_parse_bytes_invalid() {
+    let (msg, decoded) = Message::parse_bytes(b"PRIVMSG #chan :a\xffb\r\n", &DecodePolicy::default()).unwrap();
+    assert_eq!(msg.args[1], "a\u{fffd}b");
+    assert_eq!(decoded, Decoded::Lossy);
+    let policy = DecodePolicy::default().strict();
+    assert_eq!(Message::parse_bytes(b"PRIVMSG #chan :a\xffb\r\n", &policy), Err(LineError::Decode));
+    assert_eq!(Message::parse_bytes(b"\r\n", &policy), Err(LineError::Parse(ParseError::EmptyMessage)));
+}
+
+#[test]
+fn test_encode() {
+    use encoding::all::ISO_8859_1;
+
+    let mut policy = EncodePolicy::new(ISO_8859_1);
+    assert_eq!(policy.encode("été €"), Some(b"\xe9t\xe9 ?".to_vec()));
+    policy.trap = EncoderTrap::NcrEscape;
+    assert_eq!(policy.encode("€"), Some(b"&#8364;".to_vec()));
+    policy.trap = EncoderTrap::Strict;
+    assert_eq!(policy.encode("€"), None);
+}
@@ -4,6 +4,17 @@
 use std::fmt;
 use std::str;
 
+/// Kind of a code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CodeKind {
+    /// Command, such as `PRIVMSG`.
+    Command,
+    /// Numeric reply.
+    Reply,
+    /// Numeric error.
+    Error,
+}
+
 /// Representation of IRC commands, replies and errors.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Code {
@@ -97,6 +108,30 @@ pub enum Code {
     Userhost,
     /// ISON = "ISON"
     Ison,
+    /// CAP = "CAP"
+    Cap,
+    /// AUTHENTICATE = "AUTHENTICATE"
+    Authenticate,
+    /// TAGMSG = "TAGMSG"
+    Tagmsg,
+    /// BATCH = "BATCH"
+    Batch,
+    /// ACCOUNT = "ACCOUNT"
+    Account,
+    /// CHGHOST = "CHGHOST"
+    Chghost,
+    /// SETNAME = "SETNAME"
+    Setname,
+    /// FAIL = "FAIL"
+    Fail,
+    /// WARN = "WARN"
+    Warn,
+    /// NOTE = "NOTE"
+    Note,
+    /// MONITOR = "MONITOR"
+    Monitor,
+    /// STARTTLS = "STARTTLS"
+    Starttls,
     /// RPL_WELCOME = "001"
     RplWelcome,
     /// RPL_YOURHOST = "002"
@@ -105,7 +140,9 @@ pub enum Code {
     RplCreated,
     /// RPL_MYINFO = "004"
     RplMyinfo,
-    /// RPL_BOUNCE = "005"
+    /// RPL_ISUPPORT = "005"
+    RplIsupport,
+    /// RPL_BOUNCE = "010"
     RplBounce,
     /// RPL_USERHOST = "302"
     RplUserhost,
@@ -265,6 +302,58 @@ pub enum Code {
     RplAdminemail,
     /// RPL_TRYAGAIN = "263"
     RplTryagain,
+    /// RPL_LOCALUSERS = "265"
+    RplLocalusers,
+    /// RPL_GLOBALUSERS = "266"
+    RplGlobalusers,
+    /// RPL_WHOISCERTFP = "276"
+    RplWhoiscertfp,
+    /// RPL_NONE = "300"
+    RplNone,
+    /// RPL_WHOISREGNICK = "307"
+    RplWhoisregnick,
+    /// RPL_CREATIONTIME = "329"
+    RplCreationtime,
+    /// RPL_WHOISACCOUNT = "330"
+    RplWhoisaccount,
+    /// RPL_TOPICWHOTIME = "333"
+    RplTopicwhotime,
+    /// RPL_WHOISBOT = "335"
+    RplWhoisbot,
+    /// RPL_WHOISACTUALLY = "338"
+    RplWhoisactually,
+    /// RPL_WHOSPCRPL = "354"
+    RplWhospcrpl,
+    /// RPL_WHOISHOST = "378"
+    RplWhoishost,
+    /// RPL_WHOISMODES = "379"
+    RplWhoismodes,
+    /// RPL_STARTTLS = "670"
+    RplStarttls,
+    /// RPL_WHOISSECURE = "671"
+    RplWhoissecure,
+    /// RPL_HELPSTART = "704"
+    RplHelpstart,
+    /// RPL_HELPTXT = "705"
+    RplHelptxt,
+    /// RPL_ENDOFHELP = "706"
+    RplEndofhelp,
+    /// RPL_MONONLINE = "730"
+    RplMononline,
+    /// RPL_MONOFFLINE = "731"
+    RplMonoffline,
+    /// RPL_MONLIST = "732"
+    RplMonlist,
+    /// RPL_ENDOFMONLIST = "733"
+    RplEndofmonlist,
+    /// RPL_LOGGEDIN = "900"
+    RplLoggedin,
+    /// RPL_LOGGEDOUT = "901"
+    RplLoggedout,
+    /// RPL_SASLSUCCESS = "903"
+    RplSaslsuccess,
+    /// RPL_SASLMECHS = "908"
+    RplSaslmechs,
     /// ERR_NOSUCHNICK = "401"
     ErrNosuchnick,
     /// ERR_NOSUCHSERVER = "402"
@@ -371,163 +460,1351 @@ pub enum Code {
     ErrUmodeunknownflag,
     /// ERR_USERSDONTMATCH = "502"
     ErrUsersdontmatch,
-    /// Codes that are unknown end up in here.
+    /// ERR_UNKNOWNERROR = "400"
+    ErrUnknownerror,
+    /// ERR_INVALIDCAPCMD = "410"
+    ErrInvalidcapcmd,
+    /// ERR_INPUTTOOLONG = "417"
+    ErrInputtoolong,
+    /// ERR_HELPNOTFOUND = "524"
+    ErrHelpnotfound,
+    /// ERR_INVALIDKEY = "525"
+    ErrInvalidkey,
+    /// ERR_STARTTLS = "691"
+    ErrStarttls,
+    /// ERR_INVALIDMODEPARAM = "696"
+    ErrInvalidmodeparam,
+    /// ERR_NOPRIVS = "723"
+    ErrNoprivs,
+    /// ERR_MONLISTFULL = "734"
+    ErrMonlistfull,
+    /// ERR_NICKLOCKED = "902"
+    ErrNicklocked,
+    /// ERR_SASLFAIL = "904"
+    ErrSaslfail,
+    /// ERR_SASLTOOLONG = "905"
+    ErrSasltoolong,
+    /// ERR_SASLABORTED = "906"
+    ErrSaslaborted,
+    /// ERR_SASLALREADY = "907"
+    ErrSaslalready,
+    /// Numerics that are unknown end up in here.
+    Numeric(u16),
+    /// Commands that are unknown end up in here.
     Unknown(String),
 }
 
 impl Code {
 
+    /// Create the code of a numeric.
+    pub fn from_numeric(numeric: u16) -> Code {
+        match numeric {
+            1 => Code::RplWelcome,
+            2 => Code::RplYourhost,
+            3 => Code::RplCreated,
+            4 => Code::RplMyinfo,
+            5 => Code::RplIsupport,
+            10 => Code::RplBounce,
+            302 => Code::RplUserhost,
+            303 => Code::RplIson,
+            301 => Code::RplAway,
+            305 => Code::RplUnaway,
+            306 => Code::RplNowaway,
+            311 => Code::RplWhoisuser,
+            312 => Code::RplWhoisserver,
+            313 => Code::RplWhoisoperator,
+            317 => Code::RplWhoisidle,
+            318 => Code::RplEndofwhois,
+            319 => Code::RplWhoischannels,
+            314 => Code::RplWhowasuser,
+            369 => Code::RplEndofwhowas,
+            321 => Code::RplListstart,
+            322 => Code::RplList,
+            323 => Code::RplListend,
+            325 => Code::RplUniqopis,
+            324 => Code::RplChannelmodeis,
+            331 => Code::RplNotopic,
+            332 => Code::RplTopic,
+            341 => Code::RplInviting,
+            342 => Code::RplSummoning,
+            346 => Code::RplInvitelist,
+            347 => Code::RplEndofinvitelist,
+            348 => Code::RplExceptlist,
+            349 => Code::RplEndofexecptlist,
+            351 => Code::RplVersion,
+            352 => Code::RplWhoreply,
+            315 => Code::RplEndofwho,
+            353 => Code::RplNamreply,
+            366 => Code::RplEndofnames,
+            364 => Code::RplLinks,
+            365 => Code::RplEndoflinks,
+            367 => Code::RplBanlist,
+            368 => Code::RplEndofbanlist,
+            371 => Code::RplInfo,
+            374 => Code::RplEndofinfo,
+            375 => Code::RplMotdstart,
+            372 => Code::RplMotd,
+            376 => Code::RplEndofmotd,
+            381 => Code::RplYoureoper,
+            382 => Code::RplRehashing,
+            383 => Code::RplYoureservice,
+            391 => Code::RplTime,
+            392 => Code::RplUsersstart,
+            393 => Code::RplUsers,
+            394 => Code::RplEndofusers,
+            395 => Code::RplNousers,
+            200 => Code::RplTracelink,
+            201 => Code::RplTraceconnecting,
+            202 => Code::RplTracehandshake,
+            203 => Code::RplTraceuknown,
+            204 => Code::RplTraceoperator,
+            205 => Code::RplTraceuser,
+            206 => Code::RplTraceserver,
+            207 => Code::RplTraceservice,
+            208 => Code::RplTracenewtype,
+            209 => Code::RplTraceclass,
+            210 => Code::RplTracereconnect,
+            261 => Code::RplTracelog,
+            262 => Code::RplTraceend,
+            211 => Code::RplStatslinkinfo,
+            212 => Code::RplStatscommands,
+            219 => Code::RplEndofstats,
+            242 => Code::RplStatsuptime,
+            243 => Code::RplStatsoline,
+            221 => Code::RplUmodeis,
+            234 => Code::RplServlist,
+            235 => Code::RplServlistend,
+            251 => Code::RplLuserclient,
+            252 => Code::RplLuserop,
+            253 => Code::RplLuserunknown,
+            254 => Code::RplLuserchannels,
+            255 => Code::RplLuserme,
+            256 => Code::RplAdminme,
+            257 => Code::RplAdminloc1,
+            258 => Code::RplAdminloc2,
+            259 => Code::RplAdminemail,
+            263 => Code::RplTryagain,
+            265 => Code::RplLocalusers,
+            266 => Code::RplGlobalusers,
+            276 => Code::RplWhoiscertfp,
+            300 => Code::RplNone,
+            307 => Code::RplWhoisregnick,
+            329 => Code::RplCreationtime,
+            330 => Code::RplWhoisaccount,
+            333 => Code::RplTopicwhotime,
+            335 => Code::RplWhoisbot,
+            338 => Code::RplWhoisactually,
+            354 => Code::RplWhospcrpl,
+            378 => Code::RplWhoishost,
+            379 => Code::RplWhoismodes,
+            670 => Code::RplStarttls,
+            671 => Code::RplWhoissecure,
+            704 => Code::RplHelpstart,
+            705 => Code::RplHelptxt,
+            706 => Code::RplEndofhelp,
+            730 => Code::RplMononline,
+            731 => Code::RplMonoffline,
+            732 => Code::RplMonlist,
+            733 => Code::RplEndofmonlist,
+            900 => Code::RplLoggedin,
+            901 => Code::RplLoggedout,
+            903 => Code::RplSaslsuccess,
+            908 => Code::RplSaslmechs,
+            401 => Code::ErrNosuchnick,
+            402 => Code::ErrNosuchserver,
+            403 => Code::ErrNosuchchannel,
+            404 => Code::ErrCannotsendtochan,
+            405 => Code::ErrToomanychannels,
+            406 => Code::ErrWasnosuchnick,
+            407 => Code::ErrToomanytargets,
+            408 => Code::ErrNosuchservice,
+            409 => Code::ErrNoorigin,
+            411 => Code::ErrNorecipient,
+            412 => Code::ErrNotexttosend,
+            413 => Code::ErrNotoplevel,
+            414 => Code::ErrWildtoplevel,
+            415 => Code::ErrBadmask,
+            421 => Code::ErrUnknowncommand,
+            422 => Code::ErrNomotd,
+            423 => Code::ErrNoadmininfo,
+            424 => Code::ErrFileerror,
+            431 => Code::ErrNonicknamegiven,
+            432 => Code::ErrErroneousnickname,
+            433 => Code::ErrNicknameinuse,
+            436 => Code::ErrNickcollision,
+            437 => Code::ErrUnavailresource,
+            441 => Code::ErrUsernotinchannel,
+            442 => Code::ErrNotonchannel,
+            443 => Code::ErrUseronchannel,
+            444 => Code::ErrNologin,
+            445 => Code::ErrSummondisabled,
+            446 => Code::ErrUsersdisabled,
+            451 => Code::ErrNotregistered,
+            461 => Code::ErrNeedmoreparams,
+            462 => Code::ErrAlreadyregistred,
+            463 => Code::ErrNopermforhost,
+            464 => Code::ErrPasswdmismatch,
+            465 => Code::ErrYourebannedcreep,
+            466 => Code::ErrYouwillbebanned,
+            467 => Code::ErrKeyset,
+            471 => Code::ErrChannelisfull,
+            472 => Code::ErrUnknownmode,
+            473 => Code::ErrInviteonlychan,
+            474 => Code::ErrBannedfromchan,
+            475 => Code::ErrBadchannelkey,
+            476 => Code::ErrBadchanmask,
+            477 => Code::ErrNochanmodes,
+            478 => Code::ErrBanlistfull,
+            481 => Code::ErrNoprivileges,
+            482 => Code::ErrChanoprivsneeded,
+            483 => Code::ErrCantkillserver,
+            484 => Code::ErrRestricted,
+            485 => Code::ErrUniqopprivsneeded,
+            491 => Code::ErrNooperhost,
+            501 => Code::ErrUmodeunknownflag,
+            502 => Code::ErrUsersdontmatch,
+            400 => Code::ErrUnknownerror,
+            410 => Code::ErrInvalidcapcmd,
+            417 => Code::ErrInputtoolong,
+            524 => Code::ErrHelpnotfound,
+            525 => Code::ErrInvalidkey,
+            691 => Code::ErrStarttls,
+            696 => Code::ErrInvalidmodeparam,
+            723 => Code::ErrNoprivs,
+            734 => Code::ErrMonlistfull,
+            902 => Code::ErrNicklocked,
+            904 => Code::ErrSaslfail,
+            905 => Code::ErrSasltoolong,
+            906 => Code::ErrSaslaborted,
+            907 => Code::ErrSaslalready,
+            _ => Code::Numeric(numeric),
+        }
+    }
+
+    /// Get the number of a numeric reply or error.
+    pub fn numeric(&self) -> Option<u16> {
+        match *self {
+            Code::RplWelcome => Some(1),
+            Code::RplYourhost => Some(2),
+            Code::RplCreated => Some(3),
+            Code::RplMyinfo => Some(4),
+            Code::RplIsupport => Some(5),
+            Code::RplBounce => Some(10),
+            Code::RplUserhost => Some(302),
+            Code::RplIson => Some(303),
+            Code::RplAway => Some(301),
+            Code::RplUnaway => Some(305),
+            Code::RplNowaway => Some(306),
+            Code::RplWhoisuser => Some(311),
+            Code::RplWhoisserver => Some(312),
+            Code::RplWhoisoperator => Some(313),
+            Code::RplWhoisidle => Some(317),
+            Code::RplEndofwhois => Some(318),
+            Code::RplWhoischannels => Some(319),
+            Code::RplWhowasuser => Some(314),
+            Code::RplEndofwhowas => Some(369),
+            Code::RplListstart => Some(321),
+            Code::RplList => Some(322),
+            Code::RplListend => Some(323),
+            Code::RplUniqopis => Some(325),
+            Code::RplChannelmodeis => Some(324),
+            Code::RplNotopic => Some(331),
+            Code::RplTopic => Some(332),
+            Code::RplInviting => Some(341),
+            Code::RplSummoning => Some(342),
+            Code::RplInvitelist => Some(346),
+            Code::RplEndofinvitelist => Some(347),
+            Code::RplExceptlist => Some(348),
+            Code::RplEndofexecptlist => Some(349),
+            Code::RplVersion => Some(351),
+            Code::RplWhoreply => Some(352),
+            Code::RplEndofwho => Some(315),
+            Code::RplNamreply => Some(353),
+            Code::RplEndofnames => Some(366),
+            Code::RplLinks => Some(364),
+            Code::RplEndoflinks => Some(365),
+            Code::RplBanlist => Some(367),
+            Code::RplEndofbanlist => Some(368),
+            Code::RplInfo => Some(371),
+            Code::RplEndofinfo => Some(374),
+            Code::RplMotdstart => Some(375),
+            Code::RplMotd => Some(372),
+            Code::RplEndofmotd => Some(376),
+            Code::RplYoureoper => Some(381),
+            Code::RplRehashing => Some(382),
+            Code::RplYoureservice => Some(383),
+            Code::RplTime => Some(391),
+            Code::RplUsersstart => Some(392),
+            Code::RplUsers => Some(393),
+            Code::RplEndofusers => Some(394),
+            Code::RplNousers => Some(395),
+            Code::RplTracelink => Some(200),
+            Code::RplTraceconnecting => Some(201),
+            Code::RplTracehandshake => Some(202),
+            Code::RplTraceuknown => Some(203),
+            Code::RplTraceoperator => Some(204),
+            Code::RplTraceuser => Some(205),
+            Code::RplTraceserver => Some(206),
+            Code::RplTraceservice => Some(207),
+            Code::RplTracenewtype => Some(208),
+            Code::RplTraceclass => Some(209),
+            Code::RplTracereconnect => Some(210),
+            Code::RplTracelog => Some(261),
+            Code::RplTraceend => Some(262),
+            Code::RplStatslinkinfo => Some(211),
+            Code::RplStatscommands => Some(212),
+            Code::RplEndofstats => Some(219),
+            Code::RplStatsuptime => Some(242),
+            Code::RplStatsoline => Some(243),
+            Code::RplUmodeis => Some(221),
+            Code::RplServlist => Some(234),
+            Code::RplServlistend => Some(235),
+            Code::RplLuserclient => Some(251),
+            Code::RplLuserop => Some(252),
+            Code::RplLuserunknown => Some(253),
+            Code::RplLuserchannels => Some(254),
+            Code::RplLuserme => Some(255),
+            Code::RplAdminme => Some(256),
+            Code::RplAdminloc1 => Some(257),
+            Code::RplAdminloc2 => Some(258),
+            Code::RplAdminemail => Some(259),
+            Code::RplTryagain => Some(263),
+            Code::RplLocalusers => Some(265),
+            Code::RplGlobalusers => Some(266),
+            Code::RplWhoiscertfp => Some(276),
+            Code::RplNone => Some(300),
+            Code::RplWhoisregnick => Some(307),
+            Code::RplCreationtime => Some(329),
+            Code::RplWhoisaccount => Some(330),
+            Code::RplTopicwhotime => Some(333),
+            Code::RplWhoisbot => Some(335),
+            Code::RplWhoisactually => Some(338),
+            Code::RplWhospcrpl => Some(354),
+            Code::RplWhoishost => Some(378),
+            Code::RplWhoismodes => Some(379),
+            Code::RplStarttls => Some(670),
+            Code::RplWhoissecure => Some(671),
+            Code::RplHelpstart => Some(704),
+            Code::RplHelptxt => Some(705),
+            Code::RplEndofhelp => Some(706),
+            Code::RplMononline => Some(730),
+            Code::RplMonoffline => Some(731),
+            Code::RplMonlist => Some(732),
+            Code::RplEndofmonlist => Some(733),
+            Code::RplLoggedin => Some(900),
+            Code::RplLoggedout => Some(901),
+            Code::RplSaslsuccess => Some(903),
+            Code::RplSaslmechs => Some(908),
+            Code::ErrNosuchnick => Some(401),
+            Code::ErrNosuchserver => Some(402),
+            Code::ErrNosuchchannel => Some(403),
+            Code::ErrCannotsendtochan => Some(404),
+            Code::ErrToomanychannels => Some(405),
+            Code::ErrWasnosuchnick => Some(406),
+            Code::ErrToomanytargets => Some(407),
+            Code::ErrNosuchservice => Some(408),
+            Code::ErrNoorigin => Some(409),
+            Code::ErrNorecipient => Some(411),
+            Code::ErrNotexttosend => Some(412),
+            Code::ErrNotoplevel => Some(413),
+            Code::ErrWildtoplevel => Some(414),
+            Code::ErrBadmask => Some(415),
+            Code::ErrUnknowncommand => Some(421),
+            Code::ErrNomotd => Some(422),
+            Code::ErrNoadmininfo => Some(423),
+            Code::ErrFileerror => Some(424),
+            Code::ErrNonicknamegiven => Some(431),
+            Code::ErrErroneousnickname => Some(432),
+            Code::ErrNicknameinuse => Some(433),
+            Code::ErrNickcollision => Some(436),
+            Code::ErrUnavailresource => Some(437),
+            Code::ErrUsernotinchannel => Some(441),
+            Code::ErrNotonchannel => Some(442),
+            Code::ErrUseronchannel => Some(443),
+            Code::ErrNologin => Some(444),
+            Code::ErrSummondisabled => Some(445),
+            Code::ErrUsersdisabled => Some(446),
+            Code::ErrNotregistered => Some(451),
+            Code::ErrNeedmoreparams => Some(461),
+            Code::ErrAlreadyregistred => Some(462),
+            Code::ErrNopermforhost => Some(463),
+            Code::ErrPasswdmismatch => Some(464),
+            Code::ErrYourebannedcreep => Some(465),
+            Code::ErrYouwillbebanned => Some(466),
+            Code::ErrKeyset => Some(467),
+            Code::ErrChannelisfull => Some(471),
+            Code::ErrUnknownmode => Some(472),
+            Code::ErrInviteonlychan => Some(473),
+            Code::ErrBannedfromchan => Some(474),
+            Code::ErrBadchannelkey => Some(475),
+            Code::ErrBadchanmask => Some(476),
+            Code::ErrNochanmodes => Some(477),
+            Code::ErrBanlistfull => Some(478),
+            Code::ErrNoprivileges => Some(481),
+            Code::ErrChanoprivsneeded => Some(482),
+            Code::ErrCantkillserver => Some(483),
+            Code::ErrRestricted => Some(484),
+            Code::ErrUniqopprivsneeded => Some(485),
+            Code::ErrNooperhost => Some(491),
+            Code::ErrUmodeunknownflag => Some(501),
+            Code::ErrUsersdontmatch => Some(502),
+            Code::ErrUnknownerror => Some(400),
+            Code::ErrInvalidcapcmd => Some(410),
+            Code::ErrInputtoolong => Some(417),
+            Code::ErrHelpnotfound => Some(524),
+            Code::ErrInvalidkey => Some(525),
+            Code::ErrStarttls => Some(691),
+            Code::ErrInvalidmodeparam => Some(696),
+            Code::ErrNoprivs => Some(723),
+            Code::ErrMonlistfull => Some(734),
+            Code::ErrNicklocked => Some(902),
+            Code::ErrSaslfail => Some(904),
+            Code::ErrSasltoolong => Some(905),
+            Code::ErrSaslaborted => Some(906),
+            Code::ErrSaslalready => Some(907),
+            Code::Numeric(numeric) => Some(numeric),
+            _ => None,
+        }
+    }
+
+    /// Get the kind of the code.
+    ///
+    /// Unknown numerics from 400 to 599 are errors, the other ones are replies.
+    pub fn kind(&self) -> CodeKind {
+        if self.is_error() {
+            CodeKind::Error
+        } else if self.is_reply() {
+            CodeKind::Reply
+        } else {
+            CodeKind::Command
+        }
+    }
+
     /// Checks if the code is a reply.
     pub fn is_reply(&self) -> bool {
         match *self {
-            Code::RplWelcome => true,
-            Code::RplYourhost => true,
-            Code::RplCreated => true,
-            Code::RplMyinfo => true,
-            Code::RplBounce => true,
-            Code::RplUserhost => true,
-            Code::RplIson => true,
-            Code::RplAway => true,
-            Code::RplUnaway => true,
-            Code::RplNowaway => true,
-            Code::RplWhoisuser => true,
-            Code::RplWhoisserver => true,
-            Code::RplWhoisoperator => true,
-            Code::RplWhoisidle => true,
-            Code::RplEndofwhois => true,
-            Code::RplWhoischannels => true,
-            Code::RplWhowasuser => true,
-            Code::RplEndofwhowas => true,
-            Code::RplListstart => true,
-            Code::RplList => true,
-            Code::RplListend => true,
-            Code::RplUniqopis => true,
-            Code::RplChannelmodeis => true,
-            Code::RplNotopic => true,
-            Code::RplTopic => true,
-            Code::RplInviting => true,
-            Code::RplSummoning => true,
-            Code::RplInvitelist => true,
-            Code::RplEndofinvitelist => true,
-            Code::RplExceptlist => true,
-            Code::RplEndofexecptlist => true,
-            Code::RplVersion => true,
-            Code::RplWhoreply => true,
-            Code::RplEndofwho => true,
-            Code::RplNamreply => true,
-            Code::RplEndofnames => true,
-            Code::RplLinks => true,
-            Code::RplEndoflinks => true,
-            Code::RplBanlist => true,
-            Code::RplEndofbanlist => true,
-            Code::RplInfo => true,
-            Code::RplEndofinfo => true,
-            Code::RplMotdstart => true,
-            Code::RplMotd => true,
-            Code::RplEndofmotd => true,
-            Code::RplYoureoper => true,
-            Code::RplRehashing => true,
-            Code::RplYoureservice => true,
-            Code::RplTime => true,
-            Code::RplUsersstart => true,
-            Code::RplUsers => true,
-            Code::RplEndofusers => true,
-            Code::RplNousers => true,
-            Code::RplTracelink => true,
-            Code::RplTraceconnecting => true,
-            Code::RplTracehandshake => true,
-            Code::RplTraceuknown => true,
-            Code::RplTraceoperator => true,
-            Code::RplTraceuser => true,
-            Code::RplTraceserver => true,
-            Code::RplTraceservice => true,
-            Code::RplTracenewtype => true,
-            Code::RplTraceclass => true,
-            Code::RplTracereconnect => true,
-            Code::RplTracelog => true,
-            Code::RplTraceend => true,
-            Code::RplStatslinkinfo => true,
-            Code::RplStatscommands => true,
-            Code::RplEndofstats => true,
-            Code::RplStatsuptime => true,
-            Code::RplStatsoline => true,
-            Code::RplUmodeis => true,
-            Code::RplServlist => true,
-            Code::RplServlistend => true,
-            Code::RplLuserclient => true,
-            Code::RplLuserop => true,
-            Code::RplLuserunknown => true,
-            Code::RplLuserchannels => true,
-            Code::RplLuserme => true,
-            Code::RplAdminme => true,
-            Code::RplAdminloc1 => true,
-            Code::RplAdminloc2 => true,
-            Code::RplAdminemail => true,
-            Code::RplTryagain => true,
-            _  => false,
+            Code::Numeric(numeric) => !(400..600).contains(&numeric),
+            Code::RplWelcome |
+            Code::RplYourhost |
+            Code::RplCreated |
+            Code::RplMyinfo |
+            Code::RplIsupport |
+            Code::RplBounce |
+            Code::RplUserhost |
+            Code::RplIson |
+            Code::RplAway |
+            Code::RplUnaway |
+            Code::RplNowaway |
+            Code::RplWhoisuser |
+            Code::RplWhoisserver |
+            Code::RplWhoisoperator |
+            Code::RplWhoisidle |
+            Code::RplEndofwhois |
+            Code::RplWhoischannels |
+            Code::RplWhowasuser |
+            Code::RplEndofwhowas |
+            Code::RplListstart |
+            Code::RplList |
+            Code::RplListend |
+            Code::RplUniqopis |
+            Code::RplChannelmodeis |
+            Code::RplNotopic |
+            Code::RplTopic |
+            Code::RplInviting |
+            Code::RplSummoning |
+            Code::RplInvitelist |
+            Code::RplEndofinvitelist |
+            Code::RplExceptlist |
+            Code::RplEndofexecptlist |
+            Code::RplVersion |
+            Code::RplWhoreply |
+            Code::RplEndofwho |
+            Code::RplNamreply |
+            Code::RplEndofnames |
+            Code::RplLinks |
+            Code::RplEndoflinks |
+            Code::RplBanlist |
+            Code::RplEndofbanlist |
+            Code::RplInfo |
+            Code::RplEndofinfo |
+            Code::RplMotdstart |
+            Code::RplMotd |
+            Code::RplEndofmotd |
+            Code::RplYoureoper |
+            Code::RplRehashing |
+            Code::RplYoureservice |
+            Code::RplTime |
+            Code::RplUsersstart |
+            Code::RplUsers |
+            Code::RplEndofusers |
+            Code::RplNousers |
+            Code::RplTracelink |
+            Code::RplTraceconnecting |
+            Code::RplTracehandshake |
+            Code::RplTraceuknown |
+            Code::RplTraceoperator |
+            Code::RplTraceuser |
+            Code::RplTraceserver |
+            Code::RplTraceservice |
+            Code::RplTracenewtype |
+            Code::RplTraceclass |
+            Code::RplTracereconnect |
+            Code::RplTracelog |
+            Code::RplTraceend |
+            Code::RplStatslinkinfo |
+            Code::RplStatscommands |
+            Code::RplEndofstats |
+            Code::RplStatsuptime |
+            Code::RplStatsoline |
+            Code::RplUmodeis |
+            Code::RplServlist |
+            Code::RplServlistend |
+            Code::RplLuserclient |
+            Code::RplLuserop |
+            Code::RplLuserunknown |
+            Code::RplLuserchannels |
+            Code::RplLuserme |
+            Code::RplAdminme |
+            Code::RplAdminloc1 |
+            Code::RplAdminloc2 |
+            Code::RplAdminemail |
+            Code::RplTryagain |
+            Code::RplLocalusers |
+            Code::RplGlobalusers |
+            Code::RplWhoiscertfp |
+            Code::RplNone |
+            Code::RplWhoisregnick |
+            Code::RplCreationtime |
+            Code::RplWhoisaccount |
+            Code::RplTopicwhotime |
+            Code::RplWhoisbot |
+            Code::RplWhoisactually |
+            Code::RplWhospcrpl |
+            Code::RplWhoishost |
+            Code::RplWhoismodes |
+            Code::RplStarttls |
+            Code::RplWhoissecure |
+            Code::RplHelpstart |
+            Code::RplHelptxt |
+            Code::RplEndofhelp |
+            Code::RplMononline |
+            Code::RplMonoffline |
+            Code::RplMonlist |
+            Code::RplEndofmonlist |
+            Code::RplLoggedin |
+            Code::RplLoggedout |
+            Code::RplSaslsuccess |
+            Code::RplSaslmechs => true,
+            _ => false,
         }
     }
 
     /// Check if the code is en error.
     pub fn is_error(&self) -> bool {
         match *self {
-            Code::ErrNosuchnick => true,
-            Code::ErrNosuchserver => true,
-            Code::ErrNosuchchannel => true,
-            Code::ErrCannotsendtochan => true,
-            Code::ErrToomanychannels => true,
-            Code::ErrWasnosuchnick => true,
-            Code::ErrToomanytargets => true,
-            Code::ErrNosuchservice => true,
-            Code::ErrNoorigin => true,
-            Code::ErrNorecipient => true,
-            Code::ErrNotexttosend => true,
-            Code::ErrNotoplevel => true,
-            Code::ErrWildtoplevel => true,
-            Code::ErrBadmask => true,
-            Code::ErrUnknowncommand => true,
-            Code::ErrNomotd => true,
-            Code::ErrNoadmininfo => true,
-            Code::ErrFileerror => true,
-            Code::ErrNonicknamegiven => true,
-            Code::ErrErroneousnickname => true,
-            Code::ErrNicknameinuse => true,
-            Code::ErrNickcollision => true,
-            Code::ErrUnavailresource => true,
-            Code::ErrUsernotinchannel => true,
-            Code::ErrNotonchannel => true,
-            Code::ErrUseronchannel => true,
-            Code::ErrNologin => true,
-            Code::ErrSummondisabled => true,
-            Code::ErrUsersdisabled => true,
-            Code::ErrNotregistered => true,
-            Code::ErrNeedmoreparams => true,
-            Code::ErrAlreadyregistred => true,
-            Code::ErrNopermforhost => true,
-            Code::ErrPasswdmismatch => true,
-            Code::ErrYourebannedcreep => true,
-            Code::ErrYouwillbebanned => true,
-            Code::ErrKeyset => true,
-            Code::ErrChannelisfull => true,
-            Code::ErrUnknownmode => true,
-            Code::ErrInviteonlychan => true,
-            Code::ErrBannedfromchan => true,
-            Code::ErrBadchannelkey => true,
-            Code::ErrBadchanmask => true,
-            Code::ErrNochanmodes => true,
-            Code::ErrBanlistfull => true,
-            Code::ErrNoprivileges => true,
-            Code::ErrChanoprivsneeded => true,
-            Code::ErrCantkillserver => true,
-            Code::ErrRestricted => true,
-            Code::ErrUniqopprivsneeded => true,
-            Code::ErrNooperhost => true,
-            Code::ErrUmodeunknownflag => true,
-            Code::ErrUsersdontmatch => true,
-            _  => false,
+            Code::Numeric(numeric) => (400..600).contains(&numeric),
+            Code::ErrNosuchnick |
+            Code::ErrNosuchserver |
+            Code::ErrNosuchchannel |
+            Code::ErrCannotsendtochan |
+            Code::ErrToomanychannels |
+            Code::ErrWasnosuchnick |
+            Code::ErrToomanytargets |
+            Code::ErrNosuchservice |
+            Code::ErrNoorigin |
+            Code::ErrNorecipient |
+            Code::ErrNotexttosend |
+            Code::ErrNotoplevel |
+            Code::ErrWildtoplevel |
+            Code::ErrBadmask |
+            Code::ErrUnknowncommand |
+            Code::ErrNomotd |
+            Code::ErrNoadmininfo |
+            Code::ErrFileerror |
+            Code::ErrNonicknamegiven |
+            Code::ErrErroneousnickname |
+            Code::ErrNicknameinuse |
+            Code::ErrNickcollision |
+            Code::ErrUnavailresource |
+            Code::ErrUsernotinchannel |
+            Code::ErrNotonchannel |
+            Code::ErrUseronchannel |
+            Code::ErrNologin |
+            Code::ErrSummondisabled |
+            Code::ErrUsersdisabled |
+            Code::ErrNotregistered |
+            Code::ErrNeedmoreparams |
+            Code::ErrAlreadyregistred |
+            Code::ErrNopermforhost |
+            Code::ErrPasswdmismatch |
+            Code::ErrYourebannedcreep |
+            Code::ErrYouwillbebanned |
+            Code::ErrKeyset |
+            Code::ErrChannelisfull |
+            Code::ErrUnknownmode |
+            Code::ErrInviteonlychan |
+            Code::ErrBannedfromchan |
+            Code::ErrBadchannelkey |
+            Code::ErrBadchanmask |
+            Code::ErrNochanmodes |
+            Code::ErrBanlistfull |
+            Code::ErrNoprivileges |
+            Code::ErrChanoprivsneeded |
+            Code::ErrCantkillserver |
+            Code::ErrRestricted |
+            Code::ErrUniqopprivsneeded |
+            Code::ErrNooperhost |
+            Code::ErrUmodeunknownflag |
+            Code::ErrUsersdontmatch |
+            Code::ErrUnknownerror |
+            Code::ErrInvalidcapcmd |
+            Code::ErrInputtoolong |
+            Code::ErrHelpnotfound |
+            Code::ErrInvalidkey |
+            Code::ErrStarttls |
+            Code::ErrInvalidmodeparam |
+            Code::ErrNoprivs |
+            Code::ErrMonlistfull |
+            Code::ErrNicklocked |
+            Code::ErrSaslfail |
+            Code::ErrSasltoolong |
+            Code::ErrSaslaborted |
+            Code::ErrSaslalready => true,
+            _ => false,
         }
     }
 
+    /// Get the name given by the specification, such as `ERR_NICKNAMEINUSE` or `PRIVMSG`.
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match *self {
+            Code::Pass => "PASS",
+            Code::Nick => "NICK",
+            Code::User => "USER",
+            Code::Oper => "OPER",
+            Code::Mode => "MODE",
+            Code::Service => "SERVICE",
+            Code::Quit => "QUIT",
+            Code::Squit => "SQUIT",
+            Code::Join => "JOIN",
+            Code::Part => "PART",
+            Code::Topic => "TOPIC",
+            Code::Names => "NAMES",
+            Code::List => "LIST",
+            Code::Invite => "INVITE",
+            Code::Kick => "KICK",
+            Code::Privmsg => "PRIVMSG",
+            Code::Notice => "NOTICE",
+            Code::Motd => "MOTD",
+            Code::Lusers => "LUSERS",
+            Code::Version => "VERSION",
+            Code::Stats => "STATS",
+            Code::Links => "LINKS",
+            Code::Time => "TIME",
+            Code::Connect => "CONNECT",
+            Code::Trace => "TRACE",
+            Code::Admin => "ADMIN",
+            Code::Info => "INFO",
+            Code::Servlist => "SERVLIST",
+            Code::Squery => "SQUERY",
+            Code::Who => "WHO",
+            Code::Whois => "WHOIS",
+            Code::Whowas => "WHOWAS",
+            Code::Kill => "KILL",
+            Code::Ping => "PING",
+            Code::Pong => "PONG",
+            Code::Error => "ERROR",
+            Code::Away => "AWAY",
+            Code::Rehash => "REHASH",
+            Code::Die => "DIE",
+            Code::Restart => "RESTART",
+            Code::Summon => "SUMMON",
+            Code::Users => "USERS",
+            Code::Wallops => "WALLOPS",
+            Code::Userhost => "USERHOST",
+            Code::Ison => "ISON",
+            Code::Cap => "CAP",
+            Code::Authenticate => "AUTHENTICATE",
+            Code::Tagmsg => "TAGMSG",
+            Code::Batch => "BATCH",
+            Code::Account => "ACCOUNT",
+            Code::Chghost => "CHGHOST",
+            Code::Setname => "SETNAME",
+            Code::Fail => "FAIL",
+            Code::Warn => "WARN",
+            Code::Note => "NOTE",
+            Code::Monitor => "MONITOR",
+            Code::Starttls => "STARTTLS",
+            Code::RplWelcome => "RPL_WELCOME",
+            Code::RplYourhost => "RPL_YOURHOST",
+            Code::RplCreated => "RPL_CREATED",
+            Code::RplMyinfo => "RPL_MYINFO",
+            Code::RplIsupport => "RPL_ISUPPORT",
+            Code::RplBounce => "RPL_BOUNCE",
+            Code::RplUserhost => "RPL_USERHOST",
+            Code::RplIson => "RPL_ISON",
+            Code::RplAway => "RPL_AWAY",
+            Code::RplUnaway => "RPL_UNAWAY",
+            Code::RplNowaway => "RPL_NOWAWAY",
+            Code::RplWhoisuser => "RPL_WHOISUSER",
+            Code::RplWhoisserver => "RPL_WHOISSERVER",
+            Code::RplWhoisoperator => "RPL_WHOISOPERATOR",
+            Code::RplWhoisidle => "RPL_WHOISIDLE",
+            Code::RplEndofwhois => "RPL_ENDOFWHOIS",
+            Code::RplWhoischannels => "RPL_WHOISCHANNELS",
+            Code::RplWhowasuser => "RPL_WHOWASUSER",
+            Code::RplEndofwhowas => "RPL_ENDOFWHOWAS",
+            Code::RplListstart => "RPL_LISTSTART",
+            Code::RplList => "RPL_LIST",
+            Code::RplListend => "RPL_LISTEND",
+            Code::RplUniqopis => "RPL_UNIQOPIS",
+            Code::RplChannelmodeis => "RPL_CHANNELMODEIS",
+            Code::RplNotopic => "RPL_NOTOPIC",
+            Code::RplTopic => "RPL_TOPIC",
+            Code::RplInviting => "RPL_INVITING",
+            Code::RplSummoning => "RPL_SUMMONING",
+            Code::RplInvitelist => "RPL_INVITELIST",
+            Code::RplEndofinvitelist => "RPL_ENDOFINVITELIST",
+            Code::RplExceptlist => "RPL_EXCEPTLIST",
+            Code::RplEndofexecptlist => "RPL_ENDOFEXECPTLIST",
+            Code::RplVersion => "RPL_VERSION",
+            Code::RplWhoreply => "RPL_WHOREPLY",
+            Code::RplEndofwho => "RPL_ENDOFWHO",
+            Code::RplNamreply => "RPL_NAMREPLY",
+            Code::RplEndofnames => "RPL_ENDOFNAMES",
+            Code::RplLinks => "RPL_LINKS",
+            Code::RplEndoflinks => "RPL_ENDOFLINKS",
+            Code::RplBanlist => "RPL_BANLIST",
+            Code::RplEndofbanlist => "RPL_ENDOFBANLIST",
+            Code::RplInfo => "RPL_INFO",
+            Code::RplEndofinfo => "RPL_ENDOFINFO",
+            Code::RplMotdstart => "RPL_MOTDSTART",
+            Code::RplMotd => "RPL_MOTD",
+            Code::RplEndofmotd => "RPL_ENDOFMOTD",
+            Code::RplYoureoper => "RPL_YOUREOPER",
+            Code::RplRehashing => "RPL_REHASHING",
+            Code::RplYoureservice => "RPL_YOURESERVICE",
+            Code::RplTime => "RPL_TIME",
+            Code::RplUsersstart => "RPL_USERSSTART",
+            Code::RplUsers => "RPL_USERS",
+            Code::RplEndofusers => "RPL_ENDOFUSERS",
+            Code::RplNousers => "RPL_NOUSERS",
+            Code::RplTracelink => "RPL_TRACELINK",
+            Code::RplTraceconnecting => "RPL_TRACECONNECTING",
+            Code::RplTracehandshake => "RPL_TRACEHANDSHAKE",
+            Code::RplTraceuknown => "RPL_TRACEUKNOWN",
+            Code::RplTraceoperator => "RPL_TRACEOPERATOR",
+            Code::RplTraceuser => "RPL_TRACEUSER",
+            Code::RplTraceserver => "RPL_TRACESERVER",
+            Code::RplTraceservice => "RPL_TRACESERVICE",
+            Code::RplTracenewtype => "RPL_TRACENEWTYPE",
+            Code::RplTraceclass => "RPL_TRACECLASS",
+            Code::RplTracereconnect => "RPL_TRACERECONNECT",
+            Code::RplTracelog => "RPL_TRACELOG",
+            Code::RplTraceend => "RPL_TRACEEND",
+            Code::RplStatslinkinfo => "RPL_STATSLINKINFO",
+            Code::RplStatscommands => "RPL_STATSCOMMANDS",
+            Code::RplEndofstats => "RPL_ENDOFSTATS",
+            Code::RplStatsuptime => "RPL_STATSUPTIME",
+            Code::RplStatsoline => "RPL_STATSOLINE",
+            Code::RplUmodeis => "RPL_UMODEIS",
+            Code::RplServlist => "RPL_SERVLIST",
+            Code::RplServlistend => "RPL_SERVLISTEND",
+            Code::RplLuserclient => "RPL_LUSERCLIENT",
+            Code::RplLuserop => "RPL_LUSEROP",
+            Code::RplLuserunknown => "RPL_LUSERUNKNOWN",
+            Code::RplLuserchannels => "RPL_LUSERCHANNELS",
+            Code::RplLuserme => "RPL_LUSERME",
+            Code::RplAdminme => "RPL_ADMINME",
+            Code::RplAdminloc1 => "RPL_ADMINLOC1",
+            Code::RplAdminloc2 => "RPL_ADMINLOC2",
+            Code::RplAdminemail => "RPL_ADMINEMAIL",
+            Code::RplTryagain => "RPL_TRYAGAIN",
+            Code::RplLocalusers => "RPL_LOCALUSERS",
+            Code::RplGlobalusers => "RPL_GLOBALUSERS",
+            Code::RplWhoiscertfp => "RPL_WHOISCERTFP",
+            Code::RplNone => "RPL_NONE",
+            Code::RplWhoisregnick => "RPL_WHOISREGNICK",
+            Code::RplCreationtime => "RPL_CREATIONTIME",
+            Code::RplWhoisaccount => "RPL_WHOISACCOUNT",
+            Code::RplTopicwhotime => "RPL_TOPICWHOTIME",
+            Code::RplWhoisbot => "RPL_WHOISBOT",
+            Code::RplWhoisactually => "RPL_WHOISACTUALLY",
+            Code::RplWhospcrpl => "RPL_WHOSPCRPL",
+            Code::RplWhoishost => "RPL_WHOISHOST",
+            Code::RplWhoismodes => "RPL_WHOISMODES",
+            Code::RplStarttls => "RPL_STARTTLS",
+            Code::RplWhoissecure => "RPL_WHOISSECURE",
+            Code::RplHelpstart => "RPL_HELPSTART",
+            Code::RplHelptxt => "RPL_HELPTXT",
+            Code::RplEndofhelp => "RPL_ENDOFHELP",
+            Code::RplMononline => "RPL_MONONLINE",
+            Code::RplMonoffline => "RPL_MONOFFLINE",
+            Code::RplMonlist => "RPL_MONLIST",
+            Code::RplEndofmonlist => "RPL_ENDOFMONLIST",
+            Code::RplLoggedin => "RPL_LOGGEDIN",
+            Code::RplLoggedout => "RPL_LOGGEDOUT",
+            Code::RplSaslsuccess => "RPL_SASLSUCCESS",
+            Code::RplSaslmechs => "RPL_SASLMECHS",
+            Code::ErrNosuchnick => "ERR_NOSUCHNICK",
+            Code::ErrNosuchserver => "ERR_NOSUCHSERVER",
+            Code::ErrNosuchchannel => "ERR_NOSUCHCHANNEL",
+            Code::ErrCannotsendtochan => "ERR_CANNOTSENDTOCHAN",
+            Code::ErrToomanychannels => "ERR_TOOMANYCHANNELS",
+            Code::ErrWasnosuchnick => "ERR_WASNOSUCHNICK",
+            Code::ErrToomanytargets => "ERR_TOOMANYTARGETS",
+            Code::ErrNosuchservice => "ERR_NOSUCHSERVICE",
+            Code::ErrNoorigin => "ERR_NOORIGIN",
+            Code::ErrNorecipient => "ERR_NORECIPIENT",
+            Code::ErrNotexttosend => "ERR_NOTEXTTOSEND",
+            Code::ErrNotoplevel => "ERR_NOTOPLEVEL",
+            Code::ErrWildtoplevel => "ERR_WILDTOPLEVEL",
+            Code::ErrBadmask => "ERR_BADMASK",
+            Code::ErrUnknowncommand => "ERR_UNKNOWNCOMMAND",
+            Code::ErrNomotd => "ERR_NOMOTD",
+            Code::ErrNoadmininfo => "ERR_NOADMININFO",
+            Code::ErrFileerror => "ERR_FILEERROR",
+            Code::ErrNonicknamegiven => "ERR_NONICKNAMEGIVEN",
+            Code::ErrErroneousnickname => "ERR_ERRONEOUSNICKNAME",
+            Code::ErrNicknameinuse => "ERR_NICKNAMEINUSE",
+            Code::ErrNickcollision => "ERR_NICKCOLLISION",
+            Code::ErrUnavailresource => "ERR_UNAVAILRESOURCE",
+            Code::ErrUsernotinchannel => "ERR_USERNOTINCHANNEL",
+            Code::ErrNotonchannel => "ERR_NOTONCHANNEL",
+            Code::ErrUseronchannel => "ERR_USERONCHANNEL",
+            Code::ErrNologin => "ERR_NOLOGIN",
+            Code::ErrSummondisabled => "ERR_SUMMONDISABLED",
+            Code::ErrUsersdisabled => "ERR_USERSDISABLED",
+            Code::ErrNotregistered => "ERR_NOTREGISTERED",
+            Code::ErrNeedmoreparams => "ERR_NEEDMOREPARAMS",
+            Code::ErrAlreadyregistred => "ERR_ALREADYREGISTRED",
+            Code::ErrNopermforhost => "ERR_NOPERMFORHOST",
+            Code::ErrPasswdmismatch => "ERR_PASSWDMISMATCH",
+            Code::ErrYourebannedcreep => "ERR_YOUREBANNEDCREEP",
+            Code::ErrYouwillbebanned => "ERR_YOUWILLBEBANNED",
+            Code::ErrKeyset => "ERR_KEYSET",
+            Code::ErrChannelisfull => "ERR_CHANNELISFULL",
+            Code::ErrUnknownmode => "ERR_UNKNOWNMODE",
+            Code::ErrInviteonlychan => "ERR_INVITEONLYCHAN",
+            Code::ErrBannedfromchan => "ERR_BANNEDFROMCHAN",
+            Code::ErrBadchannelkey => "ERR_BADCHANNELKEY",
+            Code::ErrBadchanmask => "ERR_BADCHANMASK",
+            Code::ErrNochanmodes => "ERR_NOCHANMODES",
+            Code::ErrBanlistfull => "ERR_BANLISTFULL",
+            Code::ErrNoprivileges => "ERR_NOPRIVILEGES",
+            Code::ErrChanoprivsneeded => "ERR_CHANOPRIVSNEEDED",
+            Code::ErrCantkillserver => "ERR_CANTKILLSERVER",
+            Code::ErrRestricted => "ERR_RESTRICTED",
+            Code::ErrUniqopprivsneeded => "ERR_UNIQOPPRIVSNEEDED",
+            Code::ErrNooperhost => "ERR_NOOPERHOST",
+            Code::ErrUmodeunknownflag => "ERR_UMODEUNKNOWNFLAG",
+            Code::ErrUsersdontmatch => "ERR_USERSDONTMATCH",
+            Code::ErrUnknownerror => "ERR_UNKNOWNERROR",
+            Code::ErrInvalidcapcmd => "ERR_INVALIDCAPCMD",
+            Code::ErrInputtoolong => "ERR_INPUTTOOLONG",
+            Code::ErrHelpnotfound => "ERR_HELPNOTFOUND",
+            Code::ErrInvalidkey => "ERR_INVALIDKEY",
+            Code::ErrStarttls => "ERR_STARTTLS",
+            Code::ErrInvalidmodeparam => "ERR_INVALIDMODEPARAM",
+            Code::ErrNoprivs => "ERR_NOPRIVS",
+            Code::ErrMonlistfull => "ERR_MONLISTFULL",
+            Code::ErrNicklocked => "ERR_NICKLOCKED",
+            Code::ErrSaslfail => "ERR_SASLFAIL",
+            Code::ErrSasltoolong => "ERR_SASLTOOLONG",
+            Code::ErrSaslaborted => "ERR_SASLABORTED",
+            Code::ErrSaslalready => "ERR_SASLALREADY",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    /// Get a short description, such as `Nickname is already in use`.
+    pub fn description(&self) -> Option<&'static str> {
+        let description = match *self {
+            Code::Pass => "Set the connection password",
+            Code::Nick => "Set or change the nickname",
+            Code::User => "Set the username and real name",
+            Code::Oper => "Become an operator",
+            Code::Mode => "Query or change modes",
+            Code::Service => "Register a service",
+            Code::Quit => "Terminate the connection",
+            Code::Squit => "Disconnect a server link",
+            Code::Join => "Join channels",
+            Code::Part => "Leave channels",
+            Code::Topic => "Query or change the topic of a channel",
+            Code::Names => "List the members of channels",
+            Code::List => "List channels",
+            Code::Invite => "Invite a user to a channel",
+            Code::Kick => "Remove a user from a channel",
+            Code::Privmsg => "Send a message",
+            Code::Notice => "Send a notice",
+            Code::Motd => "Query the message of the day",
+            Code::Lusers => "Query statistics about the network",
+            Code::Version => "Query the version of the server",
+            Code::Stats => "Query statistics about the server",
+            Code::Links => "List the servers of the network",
+            Code::Time => "Query the local time of the server",
+            Code::Connect => "Connect a server to the network",
+            Code::Trace => "Find the route to a server",
+            Code::Admin => "Query the administrators of the server",
+            Code::Info => "Query information about the server",
+            Code::Servlist => "List the services",
+            Code::Squery => "Send a message to a service",
+            Code::Who => "Query information about users",
+            Code::Whois => "Query information about a user",
+            Code::Whowas => "Query information about a nickname which no longer exists",
+            Code::Kill => "Close the connection of a user",
+            Code::Ping => "Check that the connection is alive",
+            Code::Pong => "Answer a PING",
+            Code::Error => "Report a fatal error before closing the connection",
+            Code::Away => "Set or remove the away message",
+            Code::Rehash => "Reload the configuration of the server",
+            Code::Die => "Shut down the server",
+            Code::Restart => "Restart the server",
+            Code::Summon => "Ask a user to join IRC",
+            Code::Users => "List the users logged into the server",
+            Code::Wallops => "Send a message to the operators",
+            Code::Userhost => "Query the hosts of users",
+            Code::Ison => "Check if users are online",
+            Code::Cap => "Negotiate capabilities",
+            Code::Authenticate => "Authenticate with SASL",
+            Code::Tagmsg => "Send a message with tags only",
+            Code::Batch => "Start or end a batch of messages",
+            Code::Account => "A user logged in or out of an account",
+            Code::Chghost => "A user changed their username or hostname",
+            Code::Setname => "Change the real name",
+            Code::Fail => "Report that a command failed",
+            Code::Warn => "Report a warning about a command",
+            Code::Note => "Report information about a command",
+            Code::Monitor => "Track when users come online or go offline",
+            Code::Starttls => "Upgrade the connection to TLS",
+            Code::RplWelcome => "Welcome to the network",
+            Code::RplYourhost => "Your host is running this version",
+            Code::RplCreated => "This server was created at this date",
+            Code::RplMyinfo => "Server name, version and supported modes",
+            Code::RplIsupport => "Features supported by the server",
+            Code::RplBounce => "Connect to another server",
+            Code::RplUserhost => "Hosts of the requested users",
+            Code::RplIson => "Requested users which are online",
+            Code::RplAway => "User is away",
+            Code::RplUnaway => "You are no longer marked as being away",
+            Code::RplNowaway => "You have been marked as being away",
+            Code::RplWhoisuser => "WHOIS user information",
+            Code::RplWhoisserver => "WHOIS server of the user",
+            Code::RplWhoisoperator => "WHOIS user is an operator",
+            Code::RplWhoisidle => "WHOIS idle time of the user",
+            Code::RplEndofwhois => "End of WHOIS list",
+            Code::RplWhoischannels => "WHOIS channels of the user",
+            Code::RplWhowasuser => "WHOWAS user information",
+            Code::RplEndofwhowas => "End of WHOWAS",
+            Code::RplListstart => "Start of LIST",
+            Code::RplList => "LIST channel information",
+            Code::RplListend => "End of LIST",
+            Code::RplUniqopis => "Creator of the channel",
+            Code::RplChannelmodeis => "Modes of the channel",
+            Code::RplNotopic => "No topic is set",
+            Code::RplTopic => "Topic of the channel",
+            Code::RplInviting => "User was invited to the channel",
+            Code::RplSummoning => "User was summoned",
+            Code::RplInvitelist => "Invite exception of the channel",
+            Code::RplEndofinvitelist => "End of invite exception list",
+            Code::RplExceptlist => "Ban exception of the channel",
+            Code::RplEndofexecptlist => "End of ban exception list",
+            Code::RplVersion => "Version of the server",
+            Code::RplWhoreply => "WHO user information",
+            Code::RplEndofwho => "End of WHO list",
+            Code::RplNamreply => "Members of the channel",
+            Code::RplEndofnames => "End of NAMES list",
+            Code::RplLinks => "Server linked to the network",
+            Code::RplEndoflinks => "End of LINKS list",
+            Code::RplBanlist => "Ban of the channel",
+            Code::RplEndofbanlist => "End of ban list",
+            Code::RplInfo => "Information about the server",
+            Code::RplEndofinfo => "End of INFO list",
+            Code::RplMotdstart => "Start of the message of the day",
+            Code::RplMotd => "Line of the message of the day",
+            Code::RplEndofmotd => "End of the message of the day",
+            Code::RplYoureoper => "You are now an operator",
+            Code::RplRehashing => "Rehashing the configuration",
+            Code::RplYoureservice => "You are a service",
+            Code::RplTime => "Local time of the server",
+            Code::RplUsersstart => "Start of USERS list",
+            Code::RplUsers => "User logged into the server",
+            Code::RplEndofusers => "End of USERS list",
+            Code::RplNousers => "Nobody logged in",
+            Code::RplTracelink => "TRACE server link",
+            Code::RplTraceconnecting => "TRACE connection not established yet",
+            Code::RplTracehandshake => "TRACE connection in handshake",
+            Code::RplTraceuknown => "TRACE unknown connection",
+            Code::RplTraceoperator => "TRACE operator connection",
+            Code::RplTraceuser => "TRACE user connection",
+            Code::RplTraceserver => "TRACE server connection",
+            Code::RplTraceservice => "TRACE service connection",
+            Code::RplTracenewtype => "TRACE connection of a new type",
+            Code::RplTraceclass => "TRACE connection class",
+            Code::RplTracereconnect => "TRACE reconnection",
+            Code::RplTracelog => "TRACE log file",
+            Code::RplTraceend => "End of TRACE",
+            Code::RplStatslinkinfo => "STATS link information",
+            Code::RplStatscommands => "STATS command usage",
+            Code::RplEndofstats => "End of STATS report",
+            Code::RplStatsuptime => "STATS server uptime",
+            Code::RplStatsoline => "STATS operator configuration",
+            Code::RplUmodeis => "Your user modes",
+            Code::RplServlist => "Service information",
+            Code::RplServlistend => "End of service list",
+            Code::RplLuserclient => "Amount of users and servers",
+            Code::RplLuserop => "Amount of operators online",
+            Code::RplLuserunknown => "Amount of unknown connections",
+            Code::RplLuserchannels => "Amount of channels",
+            Code::RplLuserme => "Amount of users and servers connected to this server",
+            Code::RplAdminme => "Start of administrative information",
+            Code::RplAdminloc1 => "Location of the server",
+            Code::RplAdminloc2 => "Institution running the server",
+            Code::RplAdminemail => "Email address of the administrator",
+            Code::RplTryagain => "Try again later",
+            Code::RplLocalusers => "Amount of users on this server",
+            Code::RplGlobalusers => "Amount of users on the network",
+            Code::RplWhoiscertfp => "WHOIS TLS certificate fingerprint of the user",
+            Code::RplNone => "Dummy reply",
+            Code::RplWhoisregnick => "WHOIS user has a registered nickname",
+            Code::RplCreationtime => "Creation time of the channel",
+            Code::RplWhoisaccount => "WHOIS account of the user",
+            Code::RplTopicwhotime => "Who set the topic and when",
+            Code::RplWhoisbot => "WHOIS user is a bot",
+            Code::RplWhoisactually => "WHOIS actual host of the user",
+            Code::RplWhospcrpl => "WHOX user information",
+            Code::RplWhoishost => "WHOIS host of the user",
+            Code::RplWhoismodes => "WHOIS modes of the user",
+            Code::RplStarttls => "STARTTLS successful, proceed with the handshake",
+            Code::RplWhoissecure => "WHOIS user is using a secure connection",
+            Code::RplHelpstart => "Start of help",
+            Code::RplHelptxt => "Line of help",
+            Code::RplEndofhelp => "End of help",
+            Code::RplMononline => "Monitored users are online",
+            Code::RplMonoffline => "Monitored users are offline",
+            Code::RplMonlist => "Monitored users",
+            Code::RplEndofmonlist => "End of MONITOR list",
+            Code::RplLoggedin => "You are now logged in",
+            Code::RplLoggedout => "You are now logged out",
+            Code::RplSaslsuccess => "SASL authentication successful",
+            Code::RplSaslmechs => "Available SASL mechanisms",
+            Code::ErrNosuchnick => "No such nick or channel",
+            Code::ErrNosuchserver => "No such server",
+            Code::ErrNosuchchannel => "No such channel",
+            Code::ErrCannotsendtochan => "Cannot send to channel",
+            Code::ErrToomanychannels => "You have joined too many channels",
+            Code::ErrWasnosuchnick => "There was no such nickname",
+            Code::ErrToomanytargets => "Too many targets",
+            Code::ErrNosuchservice => "No such service",
+            Code::ErrNoorigin => "No origin specified",
+            Code::ErrNorecipient => "No recipient given",
+            Code::ErrNotexttosend => "No text to send",
+            Code::ErrNotoplevel => "No toplevel domain specified",
+            Code::ErrWildtoplevel => "Wildcard in toplevel domain",
+            Code::ErrBadmask => "Bad server or host mask",
+            Code::ErrUnknowncommand => "Unknown command",
+            Code::ErrNomotd => "MOTD file is missing",
+            Code::ErrNoadmininfo => "No administrative information available",
+            Code::ErrFileerror => "File error",
+            Code::ErrNonicknamegiven => "No nickname given",
+            Code::ErrErroneousnickname => "Erroneous nickname",
+            Code::ErrNicknameinuse => "Nickname is already in use",
+            Code::ErrNickcollision => "Nickname collision",
+            Code::ErrUnavailresource => "Nick or channel is temporarily unavailable",
+            Code::ErrUsernotinchannel => "User is not on that channel",
+            Code::ErrNotonchannel => "You are not on that channel",
+            Code::ErrUseronchannel => "User is already on that channel",
+            Code::ErrNologin => "User is not logged in",
+            Code::ErrSummondisabled => "SUMMON has been disabled",
+            Code::ErrUsersdisabled => "USERS has been disabled",
+            Code::ErrNotregistered => "You have not registered",
+            Code::ErrNeedmoreparams => "Not enough parameters",
+            Code::ErrAlreadyregistred => "You may not reregister",
+            Code::ErrNopermforhost => "Your host is not allowed to connect",
+            Code::ErrPasswdmismatch => "Password incorrect",
+            Code::ErrYourebannedcreep => "You are banned from this server",
+            Code::ErrYouwillbebanned => "You will be banned from this server",
+            Code::ErrKeyset => "Channel key already set",
+            Code::ErrChannelisfull => "Cannot join channel, it is full",
+            Code::ErrUnknownmode => "Unknown mode",
+            Code::ErrInviteonlychan => "Cannot join channel, it is invite only",
+            Code::ErrBannedfromchan => "Cannot join channel, you are banned",
+            Code::ErrBadchannelkey => "Cannot join channel, bad key",
+            Code::ErrBadchanmask => "Bad channel mask",
+            Code::ErrNochanmodes => "Channel does not support modes",
+            Code::ErrBanlistfull => "Channel list is full",
+            Code::ErrNoprivileges => "You are not an operator",
+            Code::ErrChanoprivsneeded => "You are not a channel operator",
+            Code::ErrCantkillserver => "You cannot kill a server",
+            Code::ErrRestricted => "Your connection is restricted",
+            Code::ErrUniqopprivsneeded => "You are not the channel creator",
+            Code::ErrNooperhost => "No operator lines for your host",
+            Code::ErrUmodeunknownflag => "Unknown user mode flag",
+            Code::ErrUsersdontmatch => "Cannot change modes of other users",
+            Code::ErrUnknownerror => "Unknown error",
+            Code::ErrInvalidcapcmd => "Invalid CAP subcommand",
+            Code::ErrInputtoolong => "Input line was too long",
+            Code::ErrHelpnotfound => "No help available on this topic",
+            Code::ErrInvalidkey => "Key is not well formed",
+            Code::ErrStarttls => "STARTTLS failed",
+            Code::ErrInvalidmodeparam => "Invalid mode parameter",
+            Code::ErrNoprivs => "Insufficient operator privileges",
+            Code::ErrMonlistfull => "MONITOR list is full",
+            Code::ErrNicklocked => "Your account is locked",
+            Code::ErrSaslfail => "SASL authentication failed",
+            Code::ErrSasltoolong => "SASL message too long",
+            Code::ErrSaslaborted => "SASL authentication aborted",
+            Code::ErrSaslalready => "You have already authenticated",
+            _ => return None,
+        };
+        Some(description)
+    }
+
+    /// Get the minimum amount of parameters of a valid message with this code.
+    ///
+    /// For replies and errors, it includes the client's nickname.
+    pub fn min_params(&self) -> Option<usize> {
+        let min_params = match *self {
+            Code::Pass => 1,
+            Code::Nick => 1,
+            Code::User => 4,
+            Code::Oper => 2,
+            Code::Mode => 1,
+            Code::Service => 6,
+            Code::Quit => 0,
+            Code::Squit => 2,
+            Code::Join => 1,
+            Code::Part => 1,
+            Code::Topic => 1,
+            Code::Names => 0,
+            Code::List => 0,
+            Code::Invite => 2,
+            Code::Kick => 2,
+            Code::Privmsg => 2,
+            Code::Notice => 2,
+            Code::Motd => 0,
+            Code::Lusers => 0,
+            Code::Version => 0,
+            Code::Stats => 0,
+            Code::Links => 0,
+            Code::Time => 0,
+            Code::Connect => 1,
+            Code::Trace => 0,
+            Code::Admin => 0,
+            Code::Info => 0,
+            Code::Servlist => 0,
+            Code::Squery => 2,
+            Code::Who => 0,
+            Code::Whois => 1,
+            Code::Whowas => 1,
+            Code::Kill => 2,
+            Code::Ping => 1,
+            Code::Pong => 1,
+            Code::Error => 1,
+            Code::Away => 0,
+            Code::Rehash => 0,
+            Code::Die => 0,
+            Code::Restart => 0,
+            Code::Summon => 1,
+            Code::Users => 0,
+            Code::Wallops => 1,
+            Code::Userhost => 1,
+            Code::Ison => 1,
+            Code::Cap => 1,
+            Code::Authenticate => 1,
+            Code::Tagmsg => 1,
+            Code::Batch => 1,
+            Code::Account => 1,
+            Code::Chghost => 2,
+            Code::Setname => 1,
+            Code::Fail => 3,
+            Code::Warn => 3,
+            Code::Note => 3,
+            Code::Monitor => 1,
+            Code::Starttls => 0,
+            Code::RplWelcome => 2,
+            Code::RplYourhost => 2,
+            Code::RplCreated => 2,
+            Code::RplMyinfo => 5,
+            Code::RplIsupport => 3,
+            Code::RplBounce => 4,
+            Code::RplUserhost => 2,
+            Code::RplIson => 2,
+            Code::RplAway => 3,
+            Code::RplUnaway => 2,
+            Code::RplNowaway => 2,
+            Code::RplWhoisuser => 6,
+            Code::RplWhoisserver => 4,
+            Code::RplWhoisoperator => 3,
+            Code::RplWhoisidle => 4,
+            Code::RplEndofwhois => 3,
+            Code::RplWhoischannels => 3,
+            Code::RplWhowasuser => 6,
+            Code::RplEndofwhowas => 3,
+            Code::RplListstart => 1,
+            Code::RplList => 4,
+            Code::RplListend => 2,
+            Code::RplUniqopis => 3,
+            Code::RplChannelmodeis => 3,
+            Code::RplNotopic => 3,
+            Code::RplTopic => 3,
+            Code::RplInviting => 3,
+            Code::RplSummoning => 3,
+            Code::RplInvitelist => 3,
+            Code::RplEndofinvitelist => 3,
+            Code::RplExceptlist => 3,
+            Code::RplEndofexecptlist => 3,
+            Code::RplVersion => 4,
+            Code::RplWhoreply => 8,
+            Code::RplEndofwho => 3,
+            Code::RplNamreply => 4,
+            Code::RplEndofnames => 3,
+            Code::RplLinks => 4,
+            Code::RplEndoflinks => 3,
+            Code::RplBanlist => 3,
+            Code::RplEndofbanlist => 3,
+            Code::RplInfo => 2,
+            Code::RplEndofinfo => 2,
+            Code::RplMotdstart => 2,
+            Code::RplMotd => 2,
+            Code::RplEndofmotd => 2,
+            Code::RplYoureoper => 2,
+            Code::RplRehashing => 3,
+            Code::RplYoureservice => 2,
+            Code::RplTime => 3,
+            Code::RplUsersstart => 2,
+            Code::RplUsers => 2,
+            Code::RplEndofusers => 2,
+            Code::RplNousers => 2,
+            Code::RplTracelink => 5,
+            Code::RplTraceconnecting => 3,
+            Code::RplTracehandshake => 3,
+            Code::RplTraceuknown => 3,
+            Code::RplTraceoperator => 3,
+            Code::RplTraceuser => 3,
+            Code::RplTraceserver => 7,
+            Code::RplTraceservice => 5,
+            Code::RplTracenewtype => 3,
+            Code::RplTraceclass => 3,
+            Code::RplTracereconnect => 3,
+            Code::RplTracelog => 3,
+            Code::RplTraceend => 4,
+            Code::RplStatslinkinfo => 8,
+            Code::RplStatscommands => 3,
+            Code::RplEndofstats => 3,
+            Code::RplStatsuptime => 2,
+            Code::RplStatsoline => 5,
+            Code::RplUmodeis => 2,
+            Code::RplServlist => 7,
+            Code::RplServlistend => 4,
+            Code::RplLuserclient => 2,
+            Code::RplLuserop => 3,
+            Code::RplLuserunknown => 3,
+            Code::RplLuserchannels => 3,
+            Code::RplLuserme => 2,
+            Code::RplAdminme => 2,
+            Code::RplAdminloc1 => 2,
+            Code::RplAdminloc2 => 2,
+            Code::RplAdminemail => 2,
+            Code::RplTryagain => 3,
+            Code::RplLocalusers => 2,
+            Code::RplGlobalusers => 2,
+            Code::RplWhoiscertfp => 3,
+            Code::RplNone => 1,
+            Code::RplWhoisregnick => 3,
+            Code::RplCreationtime => 3,
+            Code::RplWhoisaccount => 4,
+            Code::RplTopicwhotime => 4,
+            Code::RplWhoisbot => 3,
+            Code::RplWhoisactually => 3,
+            Code::RplWhospcrpl => 2,
+            Code::RplWhoishost => 3,
+            Code::RplWhoismodes => 3,
+            Code::RplStarttls => 2,
+            Code::RplWhoissecure => 3,
+            Code::RplHelpstart => 3,
+            Code::RplHelptxt => 3,
+            Code::RplEndofhelp => 3,
+            Code::RplMononline => 2,
+            Code::RplMonoffline => 2,
+            Code::RplMonlist => 2,
+            Code::RplEndofmonlist => 2,
+            Code::RplLoggedin => 4,
+            Code::RplLoggedout => 3,
+            Code::RplSaslsuccess => 2,
+            Code::RplSaslmechs => 3,
+            Code::ErrNosuchnick => 3,
+            Code::ErrNosuchserver => 3,
+            Code::ErrNosuchchannel => 3,
+            Code::ErrCannotsendtochan => 3,
+            Code::ErrToomanychannels => 3,
+            Code::ErrWasnosuchnick => 3,
+            Code::ErrToomanytargets => 3,
+            Code::ErrNosuchservice => 3,
+            Code::ErrNoorigin => 2,
+            Code::ErrNorecipient => 2,
+            Code::ErrNotexttosend => 2,
+            Code::ErrNotoplevel => 3,
+            Code::ErrWildtoplevel => 3,
+            Code::ErrBadmask => 3,
+            Code::ErrUnknowncommand => 3,
+            Code::ErrNomotd => 2,
+            Code::ErrNoadmininfo => 3,
+            Code::ErrFileerror => 2,
+            Code::ErrNonicknamegiven => 2,
+            Code::ErrErroneousnickname => 3,
+            Code::ErrNicknameinuse => 3,
+            Code::ErrNickcollision => 3,
+            Code::ErrUnavailresource => 3,
+            Code::ErrUsernotinchannel => 4,
+            Code::ErrNotonchannel => 3,
+            Code::ErrUseronchannel => 4,
+            Code::ErrNologin => 3,
+            Code::ErrSummondisabled => 2,
+            Code::ErrUsersdisabled => 2,
+            Code::ErrNotregistered => 2,
+            Code::ErrNeedmoreparams => 3,
+            Code::ErrAlreadyregistred => 2,
+            Code::ErrNopermforhost => 2,
+            Code::ErrPasswdmismatch => 2,
+            Code::ErrYourebannedcreep => 2,
+            Code::ErrYouwillbebanned => 2,
+            Code::ErrKeyset => 3,
+            Code::ErrChannelisfull => 3,
+            Code::ErrUnknownmode => 3,
+            Code::ErrInviteonlychan => 3,
+            Code::ErrBannedfromchan => 3,
+            Code::ErrBadchannelkey => 3,
+            Code::ErrBadchanmask => 3,
+            Code::ErrNochanmodes => 3,
+            Code::ErrBanlistfull => 4,
+            Code::ErrNoprivileges => 2,
+            Code::ErrChanoprivsneeded => 3,
+            Code::ErrCantkillserver => 2,
+            Code::ErrRestricted => 2,
+            Code::ErrUniqopprivsneeded => 2,
+            Code::ErrNooperhost => 2,
+            Code::ErrUmodeunknownflag => 2,
+            Code::ErrUsersdontmatch => 2,
+            Code::ErrUnknownerror => 3,
+            Code::ErrInvalidcapcmd => 3,
+            Code::ErrInputtoolong => 2,
+            Code::ErrHelpnotfound => 3,
+            Code::ErrInvalidkey => 3,
+            Code::ErrStarttls => 2,
+            Code::ErrInvalidmodeparam => 5,
+            Code::ErrNoprivs => 3,
+            Code::ErrMonlistfull => 4,
+            Code::ErrNicklocked => 2,
+            Code::ErrSaslfail => 2,
+            Code::ErrSasltoolong => 2,
+            Code::ErrSaslaborted => 2,
+            Code::ErrSaslalready => 2,
+            _ => return None,
+        };
+        Some(min_params)
+    }
+
 }
 
 impl fmt::Display for Code {
@@ -579,11 +1856,24 @@ impl fmt::Display for Code {
             Code::Wallops => "WALLOPS",
             Code::Userhost => "USERHOST",
             Code::Ison => "ISON",
+            Code::Cap => "CAP",
+            Code::Authenticate => "AUTHENTICATE",
+            Code::Tagmsg => "TAGMSG",
+            Code::Batch => "BATCH",
+            Code::Account => "ACCOUNT",
+            Code::Chghost => "CHGHOST",
+            Code::Setname => "SETNAME",
+            Code::Fail => "FAIL",
+            Code::Warn => "WARN",
+            Code::Note => "NOTE",
+            Code::Monitor => "MONITOR",
+            Code::Starttls => "STARTTLS",
             Code::RplWelcome => "001",
             Code::RplYourhost => "002",
             Code::RplCreated => "003",
             Code::RplMyinfo => "004",
-            Code::RplBounce => "005",
+            Code::RplIsupport => "005",
+            Code::RplBounce => "010",
             Code::RplUserhost => "302",
             Code::RplIson => "303",
             Code::RplAway => "301",
@@ -663,6 +1953,32 @@ impl fmt::Display for Code {
             Code::RplAdminloc2 => "258",
             Code::RplAdminemail => "259",
             Code::RplTryagain => "263",
+            Code::RplLocalusers => "265",
+            Code::RplGlobalusers => "266",
+            Code::RplWhoiscertfp => "276",
+            Code::RplNone => "300",
+            Code::RplWhoisregnick => "307",
+            Code::RplCreationtime => "329",
+            Code::RplWhoisaccount => "330",
+            Code::RplTopicwhotime => "333",
+            Code::RplWhoisbot => "335",
+            Code::RplWhoisactually => "338",
+            Code::RplWhospcrpl => "354",
+            Code::RplWhoishost => "378",
+            Code::RplWhoismodes => "379",
+            Code::RplStarttls => "670",
+            Code::RplWhoissecure => "671",
+            Code::RplHelpstart => "704",
+            Code::RplHelptxt => "705",
+            Code::RplEndofhelp => "706",
+            Code::RplMononline => "730",
+            Code::RplMonoffline => "731",
+            Code::RplMonlist => "732",
+            Code::RplEndofmonlist => "733",
+            Code::RplLoggedin => "900",
+            Code::RplLoggedout => "901",
+            Code::RplSaslsuccess => "903",
+            Code::RplSaslmechs => "908",
             Code::ErrNosuchnick => "401",
             Code::ErrNosuchserver => "402",
             Code::ErrNosuchchannel => "403",
@@ -716,6 +2032,21 @@ impl fmt::Display for Code {
             Code::ErrNooperhost => "491",
             Code::ErrUmodeunknownflag => "501",
             Code::ErrUsersdontmatch => "502",
+            Code::ErrUnknownerror => "400",
+            Code::ErrInvalidcapcmd => "410",
+            Code::ErrInputtoolong => "417",
+            Code::ErrHelpnotfound => "524",
+            Code::ErrInvalidkey => "525",
+            Code::ErrStarttls => "691",
+            Code::ErrInvalidmodeparam => "696",
+            Code::ErrNoprivs => "723",
+            Code::ErrMonlistfull => "734",
+            Code::ErrNicklocked => "902",
+            Code::ErrSaslfail => "904",
+            Code::ErrSasltoolong => "905",
+            Code::ErrSaslaborted => "906",
+            Code::ErrSaslalready => "907",
+            Code::Numeric(numeric) => return write!(f, "{:03}", numeric),
             Code::Unknown(ref text) => &text[..],
         };
         f.write_str(text)
@@ -726,192 +2057,100 @@ impl fmt::Display for Code {
 impl str::FromStr for Code {
     type Err = ();
 
+    /// Commands are case insensitive.
     fn from_str(s: &str) -> Result<Code, ()> {
-        let code = match s {
-            "PASS" => Code::Pass,
-            "NICK" => Code::Nick,
-            "USER" => Code::User,
-            "OPER" => Code::Oper,
-            "MODE" => Code::Mode,
-            "SERVICE" => Code::Service,
-            "QUIT" => Code::Quit,
-            "SQUIT" => Code::Squit,
-            "JOIN" => Code::Join,
-            "PART" => Code::Part,
-            "TOPIC" => Code::Topic,
-            "NAMES" => Code::Names,
-            "LIST" => Code::List,
-            "INVITE" => Code::Invite,
-            "KICK" => Code::Kick,
-            "PRIVMSG" => Code::Privmsg,
-            "NOTICE" => Code::Notice,
-            "MOTD" => Code::Motd,
-            "LUSERS" => Code::Lusers,
-            "VERSION" => Code::Version,
-            "STATS" => Code::Stats,
-            "LINKS" => Code::Links,
-            "TIME" => Code::Time,
-            "CONNECT" => Code::Connect,
-            "TRACE" => Code::Trace,
-            "ADMIN" => Code::Admin,
-            "INFO" => Code::Info,
-            "SERVLIST" => Code::Servlist,
-            "SQUERY" => Code::Squery,
-            "WHO" => Code::Who,
-            "WHOIS" => Code::Whois,
-            "WHOWAS" => Code::Whowas,
-            "KILL" => Code::Kill,
-            "PING" => Code::Ping,
-            "PONG" => Code::Pong,
-            "ERROR" => Code::Error,
-            "AWAY" => Code::Away,
-            "REHASH" => Code::Rehash,
-            "DIE" => Code::Die,
-            "RESTART" => Code::Restart,
-            "SUMMON" => Code::Summon,
-            "USERS" => Code::Users,
-            "WALLOPS" => Code::Wallops,
-            "USERHOST" => Code::Userhost,
-            "ISON" => Code::Ison,
-            "001" => Code::RplWelcome,
-            "002" => Code::RplYourhost,
-            "003" => Code::RplCreated,
-            "004" => Code::RplMyinfo,
-            "005" => Code::RplBounce,
-            "302" => Code::RplUserhost,
-            "303" => Code::RplIson,
-            "301" => Code::RplAway,
-            "305" => Code::RplUnaway,
-            "306" => Code::RplNowaway,
-            "311" => Code::RplWhoisuser,
-            "312" => Code::RplWhoisserver,
-            "313" => Code::RplWhoisoperator,
-            "317" => Code::RplWhoisidle,
-            "318" => Code::RplEndofwhois,
-            "319" => Code::RplWhoischannels,
-            "314" => Code::RplWhowasuser,
-            "369" => Code::RplEndofwhowas,
-            "321" => Code::RplListstart,
-            "322" => Code::RplList,
-            "323" => Code::RplListend,
-            "325" => Code::RplUniqopis,
-            "324" => Code::RplChannelmodeis,
-            "331" => Code::RplNotopic,
-            "332" => Code::RplTopic,
-            "341" => Code::RplInviting,
-            "342" => Code::RplSummoning,
-            "346" => Code::RplInvitelist,
-            "347" => Code::RplEndofinvitelist,
-            "348" => Code::RplExceptlist,
-            "349" => Code::RplEndofexecptlist,
-            "351" => Code::RplVersion,
-            "352" => Code::RplWhoreply,
-            "315" => Code::RplEndofwho,
-            "353" => Code::RplNamreply,
-            "366" => Code::RplEndofnames,
-            "364" => Code::RplLinks,
-            "365" => Code::RplEndoflinks,
-            "367" => Code::RplBanlist,
-            "368" => Code::RplEndofbanlist,
-            "371" => Code::RplInfo,
-            "374" => Code::RplEndofinfo,
-            "375" => Code::RplMotdstart,
-            "372" => Code::RplMotd,
-            "376" => Code::RplEndofmotd,
-            "381" => Code::RplYoureoper,
-            "382" => Code::RplRehashing,
-            "383" => Code::RplYoureservice,
-            "391" => Code::RplTime,
-            "392" => Code::RplUsersstart,
-            "393" => Code::RplUsers,
-            "394" => Code::RplEndofusers,
-            "395" => Code::RplNousers,
-            "200" => Code::RplTracelink,
-            "201" => Code::RplTraceconnecting,
-            "202" => Code::RplTracehandshake,
-            "203" => Code::RplTraceuknown,
-            "204" => Code::RplTraceoperator,
-            "205" => Code::RplTraceuser,
-            "206" => Code::RplTraceserver,
-            "207" => Code::RplTraceservice,
-            "208" => Code::RplTracenewtype,
-            "209" => Code::RplTraceclass,
-            "210" => Code::RplTracereconnect,
-            "261" => Code::RplTracelog,
-            "262" => Code::RplTraceend,
-            "211" => Code::RplStatslinkinfo,
-            "212" => Code::RplStatscommands,
-            "219" => Code::RplEndofstats,
-            "242" => Code::RplStatsuptime,
-            "243" => Code::RplStatsoline,
-            "221" => Code::RplUmodeis,
-            "234" => Code::RplServlist,
-            "235" => Code::RplServlistend,
-            "251" => Code::RplLuserclient,
-            "252" => Code::RplLuserop,
-            "253" => Code::RplLuserunknown,
-            "254" => Code::RplLuserchannels,
-            "255" => Code::RplLuserme,
-            "256" => Code::RplAdminme,
-            "257" => Code::RplAdminloc1,
-            "258" => Code::RplAdminloc2,
-            "259" => Code::RplAdminemail,
-            "263" => Code::RplTryagain,
-            "401" => Code::ErrNosuchnick,
-            "402" => Code::ErrNosuchserver,
-            "403" => Code::ErrNosuchchannel,
-            "404" => Code::ErrCannotsendtochan,
-            "405" => Code::ErrToomanychannels,
-            "406" => Code::ErrWasnosuchnick,
-            "407" => Code::ErrToomanytargets,
-            "408" => Code::ErrNosuchservice,
-            "409" => Code::ErrNoorigin,
-            "411" => Code::ErrNorecipient,
-            "412" => Code::ErrNotexttosend,
-            "413" => Code::ErrNotoplevel,
-            "414" => Code::ErrWildtoplevel,
-            "415" => Code::ErrBadmask,
-            "421" => Code::ErrUnknowncommand,
-            "422" => Code::ErrNomotd,
-            "423" => Code::ErrNoadmininfo,
-            "424" => Code::ErrFileerror,
-            "431" => Code::ErrNonicknamegiven,
-            "432" => Code::ErrErroneousnickname,
-            "433" => Code::ErrNicknameinuse,
-            "436" => Code::ErrNickcollision,
-            "437" => Code::ErrUnavailresource,
-            "441" => Code::ErrUsernotinchannel,
-            "442" => Code::ErrNotonchannel,
-            "443" => Code::ErrUseronchannel,
-            "444" => Code::ErrNologin,
-            "445" => Code::ErrSummondisabled,
-            "446" => Code::ErrUsersdisabled,
-            "451" => Code::ErrNotregistered,
-            "461" => Code::ErrNeedmoreparams,
-            "462" => Code::ErrAlreadyregistred,
-            "463" => Code::ErrNopermforhost,
-            "464" => Code::ErrPasswdmismatch,
-            "465" => Code::ErrYourebannedcreep,
-            "466" => Code::ErrYouwillbebanned,
-            "467" => Code::ErrKeyset,
-            "471" => Code::ErrChannelisfull,
-            "472" => Code::ErrUnknownmode,
-            "473" => Code::ErrInviteonlychan,
-            "474" => Code::ErrBannedfromchan,
-            "475" => Code::ErrBadchannelkey,
-            "476" => Code::ErrBadchanmask,
-            "477" => Code::ErrNochanmodes,
-            "478" => Code::ErrBanlistfull,
-            "481" => Code::ErrNoprivileges,
-            "482" => Code::ErrChanoprivsneeded,
-            "483" => Code::ErrCantkillserver,
-            "484" => Code::ErrRestricted,
-            "485" => Code::ErrUniqopprivsneeded,
-            "491" => Code::ErrNooperhost,
-            "501" => Code::ErrUmodeunknownflag,
-            "502" => Code::ErrUsersdontmatch,
+        if s.len() == 3 && s.bytes().all(|b| b.is_ascii_digit()) {
+            // Three digits always fit.
+            return Ok(Code::from_numeric(s.parse().unwrap()));
+        }
+        // Uppercase on the stack, longer commands are all unknown.
+        let mut buf = [0u8; 12];
+        if s.len() > buf.len() {
+            return Ok(Code::Unknown(s.to_string()));
+        }
+        let upper = &mut buf[..s.len()];
+        upper.copy_from_slice(s.as_bytes());
+        upper.make_ascii_uppercase();
+        let code = match &upper[..] {
+            b"PASS" => Code::Pass,
+            b"NICK" => Code::Nick,
+            b"USER" => Code::User,
+            b"OPER" => Code::Oper,
+            b"MODE" => Code::Mode,
+            b"SERVICE" => Code::Service,
+            b"QUIT" => Code::Quit,
+            b"SQUIT" => Code::Squit,
+            b"JOIN" => Code::Join,
+            b"PART" => Code::Part,
+            b"TOPIC" => Code::Topic,
+            b"NAMES" => Code::Names,
+            b"LIST" => Code::List,
+            b"INVITE" => Code::Invite,
+            b"KICK" => Code::Kick,
+            b"PRIVMSG" => Code::Privmsg,
+            b"NOTICE" => Code::Notice,
+            b"MOTD" => Code::Motd,
+            b"LUSERS" => Code::Lusers,
+            b"VERSION" => Code::Version,
+            b"STATS" => Code::Stats,
+            b"LINKS" => Code::Links,
+            b"TIME" => Code::Time,
+            b"CONNECT" => Code::Connect,
+            b"TRACE" => Code::Trace,
+            b"ADMIN" => Code::Admin,
+            b"INFO" => Code::Info,
+            b"SERVLIST" => Code::Servlist,
+            b"SQUERY" => Code::Squery,
+            b"WHO" => Code::Who,
+            b"WHOIS" => Code::Whois,
+            b"WHOWAS" => Code::Whowas,
+            b"KILL" => Code::Kill,
+            b"PING" => Code::Ping,
+            b"PONG" => Code::Pong,
+            b"ERROR" => Code::Error,
+            b"AWAY" => Code::Away,
+            b"REHASH" => Code::Rehash,
+            b"DIE" => Code::Die,
+            b"RESTART" => Code::Restart,
+            b"SUMMON" => Code::Summon,
+            b"USERS" => Code::Users,
+            b"WALLOPS" => Code::Wallops,
+            b"USERHOST" => Code::Userhost,
+            b"ISON" => Code::Ison,
+            b"CAP" => Code::Cap,
+            b"AUTHENTICATE" => Code::Authenticate,
+            b"TAGMSG" => Code::Tagmsg,
+            b"BATCH" => Code::Batch,
+            b"ACCOUNT" => Code::Account,
+            b"CHGHOST" => Code::Chghost,
+            b"SETNAME" => Code::Setname,
+            b"FAIL" => Code::Fail,
+            b"WARN" => Code::Warn,
+            b"NOTE" => Code::Note,
+            b"MONITOR" => Code::Monitor,
+            b"STARTTLS" => Code::Starttls,
             _ => Code::Unknown(s.to_string()),
         };
         Ok(code)
     }
 }
+
+/// Serialized as its wire string, such as `PRIVMSG` or `001`.
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Code {
+
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Code {
+
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Code, D::Error> {
+        let text: String = ::serde::Deserialize::deserialize(deserializer)?;
+        text.parse().map_err(|()| ::serde::de::Error::custom("invalid code"))
+    }
+
+}
@@ -0,0 +1,501 @@
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use code::Code;
+use message::Message;
+
+/// Error generated when a message cannot be decoded into a typed value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The message's code cannot be decoded into this type.
+    UnexpectedCode(Code),
+    /// The message does not have enough arguments for its code.
+    NotEnoughArgs {
+        /// Minimum amount of arguments for the code.
+        expected: usize,
+        /// Amount of arguments in the message.
+        found: usize,
+    },
+    /// The argument at this position is not valid.
+    InvalidArg(usize),
+}
+
+impl fmt::Display for DecodeError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::UnexpectedCode(ref code) => write!(f, "unexpected code {}", code),
+            DecodeError::NotEnoughArgs { expected, found } => {
+                write!(f, "expected at least {} arguments, found {}", expected, found)
+            }
+            DecodeError::InvalidArg(idx) => write!(f, "invalid argument at position {}", idx),
+        }
+    }
+
+}
+
+impl error::Error for DecodeError {}
+
+/// Typed representation of the common IRC commands.
+///
+/// Decode one from a `Message` with `Command::try_from(&msg)`, and encode
+/// it back with `Message::from(cmd)`. Lists, such as channels and targets,
+/// are comma separated on the wire.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// PASS password
+    Pass {
+        /// Connection password.
+        password: String,
+    },
+    /// NICK nickname
+    Nick {
+        /// New nickname.
+        nickname: String,
+    },
+    /// USER username mode * :realname
+    User {
+        /// Username.
+        username: String,
+        /// Requested user mode, usually `0` or `8`.
+        mode: String,
+        /// Real name.
+        realname: String,
+    },
+    /// OPER name password
+    Oper {
+        /// Operator name.
+        name: String,
+        /// Operator password.
+        password: String,
+    },
+    /// MODE target [modes [params...]]
+    Mode {
+        /// Channel or nickname.
+        target: String,
+        /// Mode string and its parameters, unparsed.
+        modes: Vec<String>,
+    },
+    /// QUIT [:reason]
+    Quit {
+        /// Quit message.
+        reason: Option<String>,
+    },
+    /// JOIN channels [keys]
+    Join {
+        /// Channels to join.
+        channels: Vec<String>,
+        /// Keys of the channels, in the same order.
+        keys: Vec<String>,
+    },
+    /// PART channels [:reason]
+    Part {
+        /// Channels to leave.
+        channels: Vec<String>,
+        /// Part message.
+        reason: Option<String>,
+    },
+    /// TOPIC channel [:topic]
+    Topic {
+        /// Channel.
+        channel: String,
+        /// New topic, `None` to query it.
+        topic: Option<String>,
+    },
+    /// NAMES [channels]
+    Names {
+        /// Channels to list.
+        channels: Vec<String>,
+    },
+    /// LIST [channels]
+    List {
+        /// Channels to list.
+        channels: Vec<String>,
+    },
+    /// INVITE nickname channel
+    Invite {
+        /// Invited user.
+        nickname: String,
+        /// Channel.
+        channel: String,
+    },
+    /// KICK channel users [:reason]
+    Kick {
+        /// Channel.
+        channel: String,
+        /// Kicked users.
+        users: Vec<String>,
+        /// Kick message.
+        reason: Option<String>,
+    },
+    /// PRIVMSG targets :text
+    Privmsg {
+        /// Channels or nicknames.
+        targets: Vec<String>,
+        /// Message text.
+        text: String,
+    },
+    /// NOTICE targets :text
+    Notice {
+        /// Channels or nicknames.
+        targets: Vec<String>,
+        /// Message text.
+        text: String,
+    },
+    /// PING token [target]
+    Ping {
+        /// Token, which the other side must send back.
+        token: String,
+        /// Server which should reply.
+        target: Option<String>,
+    },
+    /// PONG server [token]
+    Pong {
+        /// Server which replied, or the token when sent by a client.
+        server: String,
+        /// Token of the ping.
+        token: Option<String>,
+    },
+    /// AWAY [:message]
+    Away {
+        /// Away message, `None` when coming back.
+        message: Option<String>,
+    },
+    /// WALLOPS :text
+    Wallops {
+        /// Message text.
+        text: String,
+    },
+    /// ERROR :message
+    Error {
+        /// Error message.
+        message: String,
+    },
+}
+
+pub(crate) fn check_args(msg: &Message, expected: usize) -> Result<(), DecodeError> {
+    if msg.args.len() < expected {
+        return Err(DecodeError::NotEnoughArgs {
+            expected,
+            found: msg.args.len(),
+        });
+    }
+    Ok(())
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',').filter(|item| !item.is_empty()).map(|item| item.into()).collect()
+}
+
+fn join_list(list: &[String]) -> String {
+    list.join(",")
+}
+
+impl<'a> TryFrom<&'a Message> for Command {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<Command, DecodeError> {
+        let args = &msg.args;
+        let opt = |idx: usize| args.get(idx).cloned();
+        let list = |idx: usize| args.get(idx).map(|arg| split_list(arg)).unwrap_or_default();
+
+        let cmd = match msg.code {
+            Code::Pass => {
+                check_args(msg, 1)?;
+                Command::Pass { password: args[0].clone() }
+            }
+            Code::Nick => {
+                check_args(msg, 1)?;
+                Command::Nick { nickname: args[0].clone() }
+            }
+            Code::User => {
+                check_args(msg, 4)?;
+                Command::User {
+                    username: args[0].clone(),
+                    mode: args[1].clone(),
+                    realname: args[3].clone(),
+                }
+            }
+            Code::Oper => {
+                check_args(msg, 2)?;
+                Command::Oper {
+                    name: args[0].clone(),
+                    password: args[1].clone(),
+                }
+            }
+            Code::Mode => {
+                check_args(msg, 1)?;
+                Command::Mode {
+                    target: args[0].clone(),
+                    modes: args[1..].to_vec(),
+                }
+            }
+            Code::Quit => Command::Quit { reason: opt(0) },
+            Code::Join => {
+                check_args(msg, 1)?;
+                Command::Join {
+                    channels: list(0),
+                    keys: list(1),
+                }
+            }
+            Code::Part => {
+                check_args(msg, 1)?;
+                Command::Part {
+                    channels: list(0),
+                    reason: opt(1),
+                }
+            }
+            Code::Topic => {
+                check_args(msg, 1)?;
+                Command::Topic {
+                    channel: args[0].clone(),
+                    topic: opt(1),
+                }
+            }
+            Code::Names => Command::Names { channels: list(0) },
+            Code::List => Command::List { channels: list(0) },
+            Code::Invite => {
+                check_args(msg, 2)?;
+                Command::Invite {
+                    nickname: args[0].clone(),
+                    channel: args[1].clone(),
+                }
+            }
+            Code::Kick => {
+                check_args(msg, 2)?;
+                Command::Kick {
+                    channel: args[0].clone(),
+                    users: list(1),
+                    reason: opt(2),
+                }
+            }
+            Code::Privmsg => {
+                check_args(msg, 2)?;
+                Command::Privmsg {
+                    targets: list(0),
+                    text: args[1].clone(),
+                }
+            }
+            Code::Notice => {
+                check_args(msg, 2)?;
+                Command::Notice {
+                    targets: list(0),
+                    text: args[1].clone(),
+                }
+            }
+            Code::Ping => {
+                check_args(msg, 1)?;
+                Command::Ping {
+                    token: args[0].clone(),
+                    target: opt(1),
+                }
+            }
+            Code::Pong => {
+                check_args(msg, 1)?;
+                Command::Pong {
+                    server: args[0].clone(),
+                    token: opt(1),
+                }
+            }
+            Code::Away => Command::Away { message: opt(0) },
+            Code::Wallops => {
+                check_args(msg, 1)?;
+                Command::Wallops { text: args[0].clone() }
+            }
+            Code::Error => {
+                check_args(msg, 1)?;
+                Command::Error { message: args[0].clone() }
+            }
+            ref code => return Err(DecodeError::UnexpectedCode(code.clone())),
+        };
+        Ok(cmd)
+    }
+}
+
+impl Command {
+
+    /// Get the code of the command.
+    pub fn code(&self) -> Code {
+        match *self {
+            Command::Pass { .. } => Code::Pass,
+            Command::Nick { .. } => Code::Nick,
+            Command::User { .. } => Code::User,
+            Command::Oper { .. } => Code::Oper,
+            Command::Mode { .. } => Code::Mode,
+            Command::Quit { .. } => Code::Quit,
+            Command::Join { .. } => Code::Join,
+            Command::Part { .. } => Code::Part,
+            Command::Topic { .. } => Code::Topic,
+            Command::Names { .. } => Code::Names,
+            Command::List { .. } => Code::List,
+            Command::Invite { .. } => Code::Invite,
+            Command::Kick { .. } => Code::Kick,
+            Command::Privmsg { .. } => Code::Privmsg,
+            Command::Notice { .. } => Code::Notice,
+            Command::Ping { .. } => Code::Ping,
+            Command::Pong { .. } => Code::Pong,
+            Command::Away { .. } => Code::Away,
+            Command::Wallops { .. } => Code::Wallops,
+            Command::Error { .. } => Code::Error,
+        }
+    }
+
+}
+
+/// Encode the command into a message without prefix nor tags.
+///
+/// Free-form text, such as reasons and message text, is always sent as a
+/// trailing parameter.
+impl From<Command> for Message {
+
+    fn from(cmd: Command) -> Message {
+        let code = cmd.code();
+        // Arguments, and whether the last one is free-form text.
+        let (args, trailing) = match cmd {
+            Command::Pass { password } => (vec![password], false),
+            Command::Nick { nickname } => (vec![nickname], false),
+            Command::User { username, mode, realname } => (vec![username, mode, "*".into(), realname], true),
+            Command::Oper { name, password } => (vec![name, password], false),
+            Command::Mode { target, modes } => {
+                let mut args = vec![target];
+                args.extend(modes);
+                (args, false)
+            }
+            Command::Quit { reason } => {
+                let trailing = reason.is_some();
+                (reason.into_iter().collect(), trailing)
+            }
+            Command::Join { channels, keys } => {
+                let mut args = vec![join_list(&channels)];
+                if !keys.is_empty() {
+                    args.push(join_list(&keys));
+                }
+                (args, false)
+            }
+            Command::Part { channels, reason } => {
+                let trailing = reason.is_some();
+                let mut args = vec![join_list(&channels)];
+                args.extend(reason);
+                (args, trailing)
+            }
+            Command::Topic { channel, topic } => {
+                let trailing = topic.is_some();
+                let mut args = vec![channel];
+                args.extend(topic);
+                (args, trailing)
+            }
+            Command::Names { channels } | Command::List { channels } => {
+                (if channels.is_empty() { vec![] } else { vec![join_list(&channels)] }, false)
+            }
+            Command::Invite { nickname, channel } => (vec![nickname, channel], false),
+            Command::Kick { channel, users, reason } => {
+                let trailing = reason.is_some();
+                let mut args = vec![channel, join_list(&users)];
+                args.extend(reason);
+                (args, trailing)
+            }
+            Command::Privmsg { targets, text } | Command::Notice { targets, text } => {
+                (vec![join_list(&targets), text], true)
+            }
+            Command::Ping { token, target } => {
+                let mut args = vec![token];
+                args.extend(target);
+                (args, false)
+            }
+            Command::Pong { server, token } => {
+                let mut args = vec![server];
+                args.extend(token);
+                (args, false)
+            }
+            Command::Away { message } => {
+                let trailing = message.is_some();
+                (message.into_iter().collect(), trailing)
+            }
+            Command::Wallops { text } => (vec![text], true),
+            Command::Error { message } => (vec![message], true),
+        };
+
+        Message {
+            tags: Vec::new(),
+            prefix: None,
+            code,
+            args,
+            trailing,
+        }
+    }
+
+}
+
+#[test]
+fn test_decode_privmsg() {
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG #a,bob :hello world").unwrap();
+    assert_eq!(Command::try_from(&msg), Ok(Command::Privmsg {
+        targets: vec!["#a".into(), "bob".into()],
+        text: "hello world".into(),
+    }));
+}
+
+#[test]
+fn test_decode_join() {
+    let msg = Message::parse("JOIN #a,#b key").unwrap();
+    assert_eq!(Command::try_from(&msg), Ok(Command::Join {
+        channels: vec!["#a".into(), "#b".into()],
+        keys: vec!["key".into()],
+    }));
+}
+
+#[test]
+fn test_decode_kick() {
+    let msg = Message::parse(":op!op@op.com KICK #chan bob").unwrap();
+    assert_eq!(Command::try_from(&msg), Ok(Command::Kick {
+        channel: "#chan".into(),
+        users: vec!["bob".into()],
+        reason: None,
+    }));
+}
+
+#[test]
+fn test_decode_errors() {
+    let msg = Message::parse("PRIVMSG #chan").unwrap();
+    assert_eq!(Command::try_from(&msg), Err(DecodeError::NotEnoughArgs { expected: 2, found: 1 }));
+    let msg = Message::parse(":irc.freenode.net 001 bob :Welcome").unwrap();
+    assert_eq!(Command::try_from(&msg), Err(DecodeError::UnexpectedCode(Code::RplWelcome)));
+}
+
+#[test]
+fn test_encode() {
+    let msg = Message::from(Command::Privmsg {
+        targets: vec!["#chan".into()],
+        text: "hi".into(),
+    });
+    assert_eq!(msg.to_string(), "PRIVMSG #chan :hi");
+    let msg = Message::from(Command::Part {
+        channels: vec!["#a".into(), "#b".into()],
+        reason: None,
+    });
+    assert_eq!(msg.to_string(), "PART #a,#b");
+}
+
+#[test]
+fn test_round_trip() {
+    let lines = [
+        "NICK bob",
+        "USER bob 8 * :Bob Bobson",
+        "MODE #chan +ov bob alice",
+        "QUIT :bye",
+        "JOIN #a,#b k1,k2",
+        "PART #a :bye",
+        "TOPIC #chan :new topic",
+        "INVITE bob #chan",
+        "KICK #chan bob,alice :bye",
+        "NOTICE bob :hi",
+        "PING token",
+        "AWAY",
+    ];
+    for line in lines.iter() {
+        let msg = Message::parse(line).unwrap();
+        let cmd = Command::try_from(&msg).unwrap();
+        assert_eq!(&Message::from(cmd).to_string(), line);
+    }
+}
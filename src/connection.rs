@@ -1,13 +1,18 @@
-use std::io::{self, BufRead, BufReader, Write};
-use std::net::{Shutdown, TcpStream};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{self, Sender, Receiver};
 use std::thread;
 
-use encoding::{DecoderTrap, EncodingRef, EncoderTrap};
-use std::time::Duration;
+use encoding::EncodingRef;
+use std::time::{Duration, Instant};
 
-use message::{Message, ParseError};
+use backoff::{self, Backoff, BackoffPolicy};
+use charset::{DecodePolicy, EncodePolicy};
+use message::{Message, MessageRef, ParseError};
+#[cfg(feature = "tls")]
+use tls::TlsSettings;
+use servers::ServerList;
+use transport::{TcpTransport, Transport, TransportWriter};
 
 /// This is the comprehensive set of events that can occur.
 #[derive(Debug)]
@@ -23,6 +28,10 @@ pub enum Event {
     /// This can probably be ignored, and it shouldn't ever happen, really.
     /// If you catch this you should probably open an issue on GitHub.
     ParseError(ParseError),
+    /// A line from the server could not be decoded with a strict `DecodePolicy`.
+    ///
+    /// The raw bytes of the line are given.
+    DecodeError(Vec<u8>),
     /// Connection was sucessfully restored.
     Reconnected,
     /// Attempting to restore connection.
@@ -34,6 +43,13 @@ pub enum Event {
     ReconnectionError(io::Error),
 }
 
+// Reasons given by `Event::Closed`.
+const CLOSED_MANUALLY: &str = "manually closed";
+const CLOSED_DO_NOT_RECONNECT: &str = "do not reconnect";
+const CLOSED_MAX_ATTEMPTS: &str = "max attempts reached";
+#[cfg(feature = "serde")]
+const CLOSE_REASONS: [&str; 3] = [CLOSED_MANUALLY, CLOSED_DO_NOT_RECONNECT, CLOSED_MAX_ATTEMPTS];
+
 /// This the receiving end of a `mpsc` channel.
 ///
 /// If is closed/dropped, the connection will also be dropped,
@@ -53,13 +69,18 @@ pub enum Error {
     ///
     /// A reconnection might be in process.
     Disconnected,
+    /// The data cannot be encoded with a strict `EncodePolicy`. Nothing was sent.
+    Unencodable,
 }
 
+// Receiving half of the connection.
+type StreamReader = BufReader<Box<dyn Read + Send>>;
+
 enum StreamStatus {
     // The stream was closed manually.
     Closed,
     // The stream is connected.
-    Connected(TcpStream),
+    Connected(Box<dyn TransportWriter>),
     // The stream is disconnected, an attempt to reconnect will be made.
     Disconnected,
 }
@@ -72,19 +93,19 @@ enum StreamStatus {
 #[derive(Clone)]
 pub struct Writer {
     stream: Arc<Mutex<StreamStatus>>,
-    encoding: EncodingRef,
+    encode: EncodePolicy,
 }
 
 impl Writer {
 
-    fn new(stream: TcpStream, encoding: EncodingRef) -> Writer {
+    fn new(stream: Box<dyn TransportWriter>, encode: EncodePolicy) -> Writer {
         Writer {
             stream: Arc::new(Mutex::new(StreamStatus::Connected(stream))),
-            encoding: encoding,
+            encode,
         }
     }
 
-    fn set_connected(&self, stream: TcpStream) {
+    fn set_connected(&self, stream: Box<dyn TransportWriter>) {
         *self.stream.lock().unwrap() = StreamStatus::Connected(stream);
     }
 
@@ -106,7 +127,7 @@ impl Writer {
                 return Err(Error::Closed);
             }
             StreamStatus::Connected(ref mut stream) => {
-                let _ = stream.shutdown(Shutdown::Both);
+                let _ = stream.shutdown();
             }
             StreamStatus::Disconnected => {
                 return Err(Error::AlreadyDisconnected);
@@ -119,10 +140,7 @@ impl Writer {
 
     /// Check if the connection was manually closed.
     pub fn is_closed(&self) -> bool {
-        match *self.stream.lock().unwrap() {
-            StreamStatus::Closed => true,
-            _ => false,
-        }
+        matches!(*self.stream.lock().unwrap(), StreamStatus::Closed)
     }
 
     /// Close the connection and stop listening for messages.
@@ -137,7 +155,7 @@ impl Writer {
                 return Err(Error::AlreadyClosed);
             }
             StreamStatus::Connected(ref mut stream) => {
-                let _ = stream.shutdown(Shutdown::Both);
+                let _ = stream.shutdown();
             }
             _ => {}
         }
@@ -149,7 +167,8 @@ impl Writer {
     /// Send a raw string to the IRC server.
     ///
     /// A new line will be not be added, so make sure that you include it.
-    /// An error will be returned if the client is disconnected.
+    /// An error will be returned if the client is disconnected, or if the
+    /// data cannot be encoded.
     pub fn raw<S: AsRef<str>>(&self, data: S) -> Result<(), Error> {
         let mut status = self.stream.lock().unwrap();
         let mut failed = false;
@@ -160,10 +179,13 @@ impl Writer {
             }
             StreamStatus::Connected(ref mut stream) => {
                 // Try to write to the stream.
-                let bytes = self.encoding.encode(data.as_ref(), EncoderTrap::Ignore).unwrap();
-                if stream.write(&bytes).is_err() {
+                let bytes = match self.encode.encode(data.as_ref()) {
+                    Some(bytes) => bytes,
+                    None => return Err(Error::Unencodable),
+                };
+                if stream.write_all(&bytes).is_err() {
                     // The write failed, shutdown the connection.
-                    let _ = stream.shutdown(Shutdown::Both);
+                    let _ = stream.shutdown();
                     failed = true;
                 }
             }
@@ -184,10 +206,10 @@ impl Writer {
 
 }
 
-impl Into<Event> for Result<Message, ParseError> {
+impl From<Result<Message, ParseError>> for Event {
 
-    fn into(self) -> Event {
-        match self {
+    fn from(res: Result<Message, ParseError>) -> Event {
+        match res {
             Ok(msg) => Event::Message(msg),
             Err(err) => Event::ParseError(err),
         }
@@ -195,11 +217,73 @@ impl Into<Event> for Result<Message, ParseError> {
 
 }
 
+// Mirror of `Event` without the IO error, used to serialize and deserialize it.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Event")]
+enum SerdeEvent<'a> {
+    Closed(::std::borrow::Cow<'a, str>),
+    Disconnected,
+    Message(::std::borrow::Cow<'a, Message>),
+    ParseError(ParseError),
+    DecodeError(::std::borrow::Cow<'a, [u8]>),
+    Reconnected,
+    Reconnecting,
+    ReconnectionError(::std::borrow::Cow<'a, str>),
+}
+
+/// `ReconnectionError` is serialized as the error's message.
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Event {
+
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let event = match *self {
+            Event::Closed(reason) => SerdeEvent::Closed(reason.into()),
+            Event::Disconnected => SerdeEvent::Disconnected,
+            Event::Message(ref msg) => SerdeEvent::Message(::std::borrow::Cow::Borrowed(msg)),
+            Event::ParseError(err) => SerdeEvent::ParseError(err),
+            Event::DecodeError(ref bytes) => SerdeEvent::DecodeError(::std::borrow::Cow::Borrowed(bytes)),
+            Event::Reconnected => SerdeEvent::Reconnected,
+            Event::Reconnecting => SerdeEvent::Reconnecting,
+            Event::ReconnectionError(ref err) => SerdeEvent::ReconnectionError(err.to_string().into()),
+        };
+        event.serialize(serializer)
+    }
+
+}
+
+/// `ReconnectionError` is deserialized as an error of kind `Other` with the message.
+/// `Closed` only accepts the reasons given by this library.
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Event {
+
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Event, D::Error> {
+        Ok(match SerdeEvent::deserialize(deserializer)? {
+            SerdeEvent::Closed(reason) => {
+                match CLOSE_REASONS.iter().find(|known| **known == reason) {
+                    Some(known) => Event::Closed(known),
+                    None => return Err(::serde::de::Error::custom(format!("unknown close reason {:?}", reason))),
+                }
+            }
+            SerdeEvent::Disconnected => Event::Disconnected,
+            SerdeEvent::Message(msg) => Event::Message(msg.into_owned()),
+            SerdeEvent::ParseError(err) => Event::ParseError(err),
+            SerdeEvent::DecodeError(bytes) => Event::DecodeError(bytes.into_owned()),
+            SerdeEvent::Reconnected => Event::Reconnected,
+            SerdeEvent::Reconnecting => Event::Reconnecting,
+            SerdeEvent::ReconnectionError(err) => {
+                Event::ReconnectionError(io::Error::other(err.into_owned()))
+            }
+        })
+    }
+
+}
+
 /// These settings tell the reconnection process how to behave.
 ///
 /// Default is implemented for this type, with fairly sensible settings.
 /// See the Default trait implementation.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ReconnectionSettings {
     /// Don't try to reconnect after failure.
     DoNotReconnect,
@@ -209,17 +293,29 @@ pub enum ReconnectionSettings {
         ///
         /// A value of 0 means infinite attempts.
         max_attempts: u32,
-        /// Wait time between two attempts to reconnect in milliseconds.
+        /// Wait time between two attempts to reconnect in milliseconds,
+        /// the base of the `backoff` policy.
         ///
-        /// Note that if the computer's network is still unavailable, the connect
-        /// call might block for about a minute until it fails. Somtimes, it fails
+        /// An attempt fails when the transport gives up, after the timeout of
+        /// `TcpTransport` and `ServerList` for instance. Sometimes, it fails
         /// instantly because it cannot resolve the hostname. You should probably
         /// leave at least a second of delay, so that it doesn't loop really fast
         /// while getting hostname resolution errors. You can watch the stream of
         /// errors via the ReconnectionError event.
         delay_between_attempts: Duration,
         /// Wait time after disconnection, before trying to reconnect.
+        ///
+        /// The wait is at least the delay given by the backoff policy. After a
+        /// healthy connection, a random delay of up to `delay_between_attempts`
+        /// is added, so that clients disconnected together do not reconnect in lockstep.
         delay_after_disconnect: Duration,
+        /// How the wait time between attempts grows after each failure.
+        backoff: Backoff,
+        /// How long a connection must stay up to be healthy, so that the backoff starts over.
+        ///
+        /// A connection dropped sooner counts as a failure, so a server which
+        /// accepts connections and drops them right away is backed off as well.
+        reset_after: Duration,
     }
 }
 
@@ -232,6 +328,10 @@ pub enum ReconnectionSettings {
 /// `delay_between_attempts` = 5 seconds
 ///
 /// `delay_after_disconnect` = 60 seconds
+///
+/// `backoff` = `Backoff::Constant`
+///
+/// `reset_after` = 5 minutes
 impl Default for ReconnectionSettings {
 
     fn default() -> ReconnectionSettings {
@@ -239,24 +339,54 @@ impl Default for ReconnectionSettings {
             max_attempts: 10,
             delay_between_attempts: Duration::from_secs(5),
             delay_after_disconnect: Duration::from_secs(60),
+            backoff: Backoff::Constant,
+            reset_after: Duration::from_secs(5 * 60),
         }
     }
 
 }
 
-fn reconnect(address: &str, handle: &Writer) -> io::Result<(BufReader<TcpStream>)> {
-    let stream = try!(TcpStream::connect(address));
-    let reader = BufReader::new(try!(stream.try_clone()));
+fn reconnect(transport: &mut dyn Transport, handle: &Writer) -> io::Result<StreamReader> {
+    let (reader, stream) = transport.connect()?;
     handle.set_connected(stream);
-    Ok((reader))
+    Ok(BufReader::new(reader))
+}
+
+// First wait after a healthy connection was dropped, spread so that clients do not reconnect together.
+fn healthy_delay(delay_after_disconnect: Duration, base: Duration, backoff: &Backoff) -> Duration {
+    delay_after_disconnect.max(backoff.delay(base, 1, base)) + backoff::jitter(base)
+}
+
+// Decode and parse a line, giving `None` when the filter rejects the message.
+fn line_event(buff: &[u8], decode: &DecodePolicy, filter: &mut LineFilter) -> Option<Event> {
+    let line = match decode.decode(buff) {
+        Some((line, _)) => line,
+        None => return Some(Event::DecodeError(buff.to_vec())),
+    };
+    match MessageRef::parse(&line) {
+        Ok(msg) if filter(&msg) => Some(Event::Message(msg.to_owned())),
+        Ok(_) => None,
+        Err(err) => Some(Event::ParseError(err)),
+    }
 }
 
-fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
+// Tells which messages are turned into events, before they are converted into owned messages.
+type LineFilter = Box<dyn FnMut(&MessageRef) -> bool + Send>;
+
+fn reader_thread(mut transport: Box<dyn Transport>, mut reader: StreamReader,
                                    event_sender: Sender<Event>, handle: Writer,
                                    reco_settings: ReconnectionSettings,
-                                   encoding: EncodingRef) {
+                                   decode: DecodePolicy, mut filter: LineFilter) {
+    // Consecutive failures and the last delay, kept until a connection is healthy.
+    let mut failures = 0u32;
+    let mut last_delay = Duration::ZERO;
+    let mut connected_at = Instant::now();
+
+    // The buffer is reused for every line.
+    let mut buff = Vec::new();
+
     'read: loop {
-        let mut buff = Vec::new();
+        buff.clear();
         let res = reader.read_until(b'\n', &mut buff);
 
         // If there's an error or a zero length read, we should check to reconnect or exit.
@@ -264,7 +394,7 @@ fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
         if res.is_err() || res.unwrap() == 0 {
             // If the stream has the closed status, the stream was manually closed.
             if handle.is_closed() {
-                let _ = event_sender.send(Event::Closed("manually closed"));
+                let _ = event_sender.send(Event::Closed(CLOSED_MANUALLY));
                 break;
             } else {
                 // The stream was not closed manually, see what we should do.
@@ -277,20 +407,31 @@ fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
                 }
 
                 // Grab the reconnection settings or break the loop if no reconnection is desired.
-                let (max_attempts, delay_between_attempts, delay_after_disconnect) = match reco_settings {
+                let (max_attempts, delay_between_attempts, delay_after_disconnect, backoff, reset_after) = match reco_settings {
                     ReconnectionSettings::DoNotReconnect => {
                         let _ = handle.close();
-                        let _ = event_sender.send(Event::Closed("do not reconnect"));
+                        let _ = event_sender.send(Event::Closed(CLOSED_DO_NOT_RECONNECT));
                         break;
                     }
                     ReconnectionSettings::Reconnect{ max_attempts,
                                                      delay_between_attempts,
-                                                     delay_after_disconnect } => {
-                        (max_attempts, delay_between_attempts, delay_after_disconnect)
+                                                     delay_after_disconnect,
+                                                     ref backoff,
+                                                     reset_after } => {
+                        (max_attempts, delay_between_attempts, delay_after_disconnect, backoff, reset_after)
                     }
                 };
 
-                thread::sleep(delay_after_disconnect);
+                // A connection which did not stay up long enough counts as a failure.
+                if connected_at.elapsed() >= reset_after {
+                    failures = 0;
+                    last_delay = delay_between_attempts;
+                    thread::sleep(healthy_delay(delay_after_disconnect, delay_between_attempts, backoff));
+                } else {
+                    failures += 1;
+                    last_delay = backoff.delay(delay_between_attempts, failures, last_delay.max(delay_between_attempts));
+                    thread::sleep(delay_after_disconnect.max(last_delay));
+                }
 
                 let mut attempts = 0u32;
 
@@ -302,7 +443,7 @@ fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
                         attempts += 1;
                         if attempts > max_attempts {
                             let _ = handle.close();
-                            let _ = event_sender.send(Event::Closed("max attempts reached"));
+                            let _ = event_sender.send(Event::Closed(CLOSED_MAX_ATTEMPTS));
                             break 'read;
                         }
                     }
@@ -312,10 +453,11 @@ fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
                     }
 
                     // Try to reconnect.
-                    match reconnect(&address, &handle) {
+                    match reconnect(&mut *transport, &handle) {
                         // Sucess, send event, and update reader.
                         Ok(new_reader) => {
                             reader = new_reader;
+                            connected_at = Instant::now();
                             if event_sender.send(Event::Reconnected).is_err() {
                                 break 'read;
                             }
@@ -330,15 +472,17 @@ fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
                         }
                     }
                     // sleep until we try to reconnect again
-                    thread::sleep(delay_between_attempts);
+                    failures += 1;
+                    last_delay = backoff.delay(delay_between_attempts, failures, last_delay.max(delay_between_attempts));
+                    thread::sleep(last_delay);
                 }
             }
         } else {
-            // decode the message
-            let line = encoding.decode(&buff, DecoderTrap::Ignore).unwrap();
-            // Size is bigger than 0, try to parse the message. Send the result in the channel.
-            if event_sender.send(Message::parse(&line).into()).is_err() {
-                break;
+            // Size is bigger than 0, try to decode and parse the message. Send the result in the channel.
+            if let Some(event) = line_event(&buff, &decode, &mut filter) {
+                if event_sender.send(event).is_err() {
+                    break;
+                }
             }
         }
     }
@@ -355,21 +499,303 @@ fn reader_thread(address: String, mut reader: BufReader<TcpStream>,
 /// A `Writer`/`Reader` pair is returned. If the connection fails,
 /// an error is returned.
 ///
+/// Lines from the server are decoded as UTF-8, falling back to the given encoding.
+/// Lines sent to the server use the given encoding, and characters it cannot
+/// represent are replaced by `?`. Use `connect_with_policies` for more control.
+///
 /// If you don't want to reconnect, use `ReconnectionSettings::DoNotReconnect`.
+/// To go through a proxy, use `connect_transport` with a `TcpTransport`.
 pub fn connect<A: AsRef<str>>(address: A, reco_settings: ReconnectionSettings, encoding: EncodingRef) -> io::Result<(Writer, Reader)> {
-    let stream = try!(TcpStream::connect(address.as_ref()));
-    let reader = BufReader::new(try!(stream.try_clone()));
+    connect_with_policies(address, reco_settings, decode_policy(encoding), EncodePolicy::new(encoding))
+}
+
+// UTF-8 first, falling back to the encoding.
+fn decode_policy(encoding: EncodingRef) -> DecodePolicy {
+    if encoding.name() == "utf-8" {
+        DecodePolicy::default()
+    } else {
+        DecodePolicy::with_fallback(encoding)
+    }
+}
+
+/// Create a connection to the given address, with the given encoding policies.
+///
+/// See `connect`.
+pub fn connect_with_policies<A: AsRef<str>>(address: A, reco_settings: ReconnectionSettings,
+                                            decode: DecodePolicy, encode: EncodePolicy)
+                                            -> io::Result<(Writer, Reader)> {
+    connect_transport(TcpTransport::new(address), reco_settings, decode, encode)
+}
+
+/// Create a connection to the first server of the list which answers.
+///
+/// Reconnections rotate through the list as well, see `ServerList` and `connect`.
+pub fn connect_servers(servers: ServerList, reco_settings: ReconnectionSettings, encoding: EncodingRef)
+                       -> io::Result<(Writer, Reader)> {
+    connect_transport(servers, reco_settings, decode_policy(encoding), EncodePolicy::new(encoding))
+}
+
+/// Create a TLS connection to the given address, available with the `tls` feature.
+///
+/// Reconnections use TLS as well. See `connect` and `TlsSettings`.
+#[cfg(feature = "tls")]
+pub fn connect_tls<A: AsRef<str>>(address: A, reco_settings: ReconnectionSettings, encoding: EncodingRef,
+                                  tls: &TlsSettings) -> io::Result<(Writer, Reader)> {
+    connect_tls_with_policies(address, reco_settings, decode_policy(encoding), EncodePolicy::new(encoding), tls)
+}
+
+/// Create a TLS connection to the given address, with the given encoding policies.
+///
+/// See `connect_tls`.
+#[cfg(feature = "tls")]
+pub fn connect_tls_with_policies<A: AsRef<str>>(address: A, reco_settings: ReconnectionSettings,
+                                                decode: DecodePolicy, encode: EncodePolicy, tls: &TlsSettings)
+                                                -> io::Result<(Writer, Reader)> {
+    connect_transport(TcpTransport::new(address).tls(tls)?, reco_settings, decode, encode)
+}
+
+/// Create a connection with the transport, with the given encoding policies.
+///
+/// The transport makes the first connection, and a new one on every reconnection.
+/// See `connect`.
+pub fn connect_transport<T: Transport>(transport: T, reco_settings: ReconnectionSettings,
+                                       decode: DecodePolicy, encode: EncodePolicy)
+                                       -> io::Result<(Writer, Reader)> {
+    connect_transport_with_filter(transport, reco_settings, decode, encode, |_: &MessageRef| true)
+}
+
+/// Create a connection with the transport, keeping only the messages accepted by the filter.
+///
+/// The filter sees each message as a `MessageRef`, borrowed from the line, before
+/// it is converted into an owned `Message`. Rejected messages do not allocate and
+/// do not produce any event. Lines which cannot be decoded or parsed are still reported.
+/// See `connect_transport`.
+pub fn connect_transport_with_filter<T, F>(mut transport: T, reco_settings: ReconnectionSettings,
+                                           decode: DecodePolicy, encode: EncodePolicy, filter: F)
+                                           -> io::Result<(Writer, Reader)>
+    where T: Transport, F: FnMut(&MessageRef) -> bool + Send + 'static
+{
+    let (reader, stream) = transport.connect()?;
 
     let (event_sender, event_reader) = mpsc::channel::<Event>();
 
-    let writer = Writer::new(stream, encoding);
+    let writer = Writer::new(stream, encode);
     // The reader thread needs a handle to modify the status.
     let reader_handle = writer.clone();
 
-    let address_clone = address.as_ref().into();
     thread::spawn(move || {
-        reader_thread(address_clone, reader, event_sender, reader_handle, reco_settings, encoding);
+        reader_thread(Box::new(transport), BufReader::new(reader), event_sender, reader_handle, reco_settings, decode,
+                      Box::new(filter));
     });
 
     Ok((writer, event_reader))
 }
+
+#[cfg(unix)]
+#[test]
+fn test_filter() {
+    use std::os::unix::net::UnixStream;
+
+    use code::Code;
+    use transport::Connection;
+
+    let (client, mut server) = UnixStream::pair().unwrap();
+    let mut client = Some(client);
+    let transport = move || -> io::Result<Connection> {
+        let client = client.take().unwrap();
+        Ok((Box::new(client.try_clone()?), Box::new(client)))
+    };
+    let filter = |msg: &MessageRef| msg.command != "PRIVMSG" || msg.arg(0) == Some("#keep");
+    let (_writer, reader) = connect_transport_with_filter(transport, ReconnectionSettings::DoNotReconnect,
+                                                          DecodePolicy::default(), EncodePolicy::default(),
+                                                          filter).unwrap();
+    server.write_all(b":bob PRIVMSG #drop :a\r\n:bob PRIVMSG #keep :b\r\n@ PING\r\nPING :x\r\n").unwrap();
+    match reader.recv().unwrap() {
+        Event::Message(msg) => assert_eq!(msg.args, vec!["#keep", "b"]),
+        event => panic!("unexpected event {:?}", event),
+    }
+    // Errors are not filtered.
+    assert!(matches!(reader.recv().unwrap(), Event::ParseError(ParseError::EmptyTags(0))));
+    match reader.recv().unwrap() {
+        Event::Message(msg) => assert_eq!(msg.code, Code::Ping),
+        event => panic!("unexpected event {:?}", event),
+    }
+}
+
+#[test]
+fn test_healthy_delay_spread() {
+    let base = Duration::from_secs(5);
+    let after = Duration::from_secs(60);
+    // Two reader threads disconnected at the same time.
+    let threads: Vec<_> = (0..2).map(|_| thread::spawn(move || healthy_delay(after, base, &Backoff::Constant))).collect();
+    let delays: Vec<Duration> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+    for delay in delays {
+        assert!(delay >= after && delay <= after + base);
+    }
+    // The backoff policy is a floor as well.
+    let backoff = Backoff::Linear { step: Duration::ZERO, max: Duration::from_secs(120) };
+    assert!(healthy_delay(Duration::ZERO, Duration::from_secs(90), &backoff) >= Duration::from_secs(90));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let event = Event::ReconnectionError(io::Error::new(io::ErrorKind::ConnectionRefused, "refused"));
+    assert_eq!(::serde_json::to_string(&event).unwrap(), r#"{"ReconnectionError":"refused"}"#);
+    match ::serde_json::from_str(r#"{"ReconnectionError":"refused"}"#).unwrap() {
+        Event::ReconnectionError(err) => assert_eq!(err.to_string(), "refused"),
+        event => panic!("unexpected event {:?}", event),
+    }
+
+    let closed = ::serde_json::to_string(&Event::Closed(CLOSED_MANUALLY)).unwrap();
+    match ::serde_json::from_str(&closed).unwrap() {
+        Event::Closed(reason) => assert_eq!(reason, CLOSED_MANUALLY),
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert!(::serde_json::from_str::<Event>(r#"{"Closed":"other"}"#).is_err());
+    assert_eq!(::serde_json::to_string(&Event::Reconnected).unwrap(), r#""Reconnected""#);
+}
+
+// Serve the connections with TLS, after STARTTLS if asked. Each client gets a welcome.
+#[cfg(all(test, feature = "tls"))]
+fn tls_server(config: ::rustls::ServerConfig, starttls: bool, connections: usize) -> u16 {
+    use std::net::TcpListener;
+    use rustls::{ServerConnection, StreamOwned};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let config = Arc::new(config);
+    thread::spawn(move || {
+        for _ in 0..connections {
+            let (mut sock, _) = listener.accept().unwrap();
+            if starttls {
+                // Read byte by byte, the handshake follows.
+                let mut line = Vec::new();
+                let mut byte = [0];
+                while line.last() != Some(&b'\n') && sock.read(&mut byte).unwrap() == 1 {
+                    line.push(byte[0]);
+                }
+                assert_eq!(line, b"STARTTLS\r\n");
+                sock.write_all(b":server NOTICE * :hello\r\n:server 670 * :STARTTLS successful\r\n").unwrap();
+            }
+            let mut conn = ServerConnection::new(config.clone()).unwrap();
+            if conn.complete_io(&mut sock).is_err() {
+                continue;
+            }
+            let mut tls = StreamOwned::new(conn, sock);
+            let welcome = match tls.conn.peer_certificates() {
+                Some(_) => ":server 001 bob :Welcome, certified\r\n",
+                None => ":server 001 bob :Welcome\r\n",
+            };
+            if tls.write_all(welcome.as_bytes()).is_err() {
+                continue;
+            }
+            // Keep the connection open until the client leaves.
+            let mut buf = [0; 512];
+            while let Ok(1..) = tls.read(&mut buf) {}
+        }
+    });
+    port
+}
+
+#[cfg(all(test, feature = "tls"))]
+fn tls_server_config(server: &::rcgen::CertifiedKey, client_root: Option<&::rcgen::CertifiedKey>) -> ::rustls::ServerConfig {
+    use rustls::RootCertStore;
+    use rustls::pki_types::PrivateKeyDer;
+    use rustls::server::WebPkiClientVerifier;
+
+    let provider = Arc::new(::rustls::crypto::ring::default_provider());
+    let builder = ::rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = match client_root {
+        Some(client) => {
+            let mut roots = RootCertStore::empty();
+            roots.add(client.cert.der().clone()).unwrap();
+            builder.with_client_cert_verifier(WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .unwrap())
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let key = PrivateKeyDer::Pkcs8(server.key_pair.serialize_der().into());
+    builder.with_single_cert(vec![server.cert.der().clone()], key).unwrap()
+}
+
+#[cfg(all(test, feature = "tls"))]
+fn next_message(reader: &Reader) -> Message {
+    loop {
+        match reader.recv_timeout(Duration::from_secs(10)).unwrap() {
+            Event::Message(msg) => return msg,
+            Event::Disconnected | Event::Reconnecting | Event::Reconnected => {}
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_root_and_reconnect() {
+    use encoding::all::UTF_8;
+
+    let server = ::rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let port = tls_server(tls_server_config(&server, None), false, 2);
+
+    let tls = TlsSettings::new().without_default_roots().add_root_certificate(server.cert.der().to_vec()).server_name("localhost");
+    let reco = ReconnectionSettings::Reconnect {
+        max_attempts: 1,
+        delay_between_attempts: Duration::from_millis(0),
+        delay_after_disconnect: Duration::from_millis(0),
+        backoff: Backoff::Constant,
+        reset_after: Duration::from_secs(5 * 60),
+    };
+    let (writer, reader) = connect_tls(format!("127.0.0.1:{}", port), reco, UTF_8, &tls).unwrap();
+    assert_eq!(next_message(&reader).args[1], "Welcome");
+    // The reconnection uses TLS as well.
+    writer.disconnect().unwrap();
+    assert_eq!(next_message(&reader).args[1], "Welcome");
+    writer.close().unwrap();
+
+    // Without the root, the server is not trusted.
+    let port = tls_server(tls_server_config(&server, None), false, 1);
+    let tls = TlsSettings::new().server_name("localhost");
+    assert!(connect_tls(format!("127.0.0.1:{}", port), ReconnectionSettings::DoNotReconnect, UTF_8, &tls).is_err());
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_pinning() {
+    use encoding::all::UTF_8;
+
+    let server = ::rcgen::generate_simple_self_signed(vec!["irc.example.com".into()]).unwrap();
+    let config = tls_server_config(&server, None);
+    let port = tls_server(config.clone(), false, 1);
+
+    let pin = TlsSettings::spki_sha256(server.cert.der()).unwrap();
+    let tls = TlsSettings::new().pin_spki_sha256(pin);
+    let (_writer, reader) = connect_tls(format!("127.0.0.1:{}", port), ReconnectionSettings::DoNotReconnect, UTF_8, &tls).unwrap();
+    assert_eq!(next_message(&reader).args[1], "Welcome");
+
+    let port = tls_server(config, false, 1);
+    let tls = TlsSettings::new().pin_spki_sha256([0; 32]);
+    assert!(connect_tls(format!("127.0.0.1:{}", port), ReconnectionSettings::DoNotReconnect, UTF_8, &tls).is_err());
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_starttls_client_certificate() {
+    use encoding::all::UTF_8;
+
+    let server = ::rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let client = ::rcgen::generate_simple_self_signed(vec!["bob".into()]).unwrap();
+    let port = tls_server(tls_server_config(&server, Some(&client)), true, 1);
+
+    let tls = TlsSettings::new()
+        .pin_spki_sha256(TlsSettings::spki_sha256(server.cert.der()).unwrap())
+        .client_certificate(vec![client.cert.der().to_vec()], client.key_pair.serialize_der()).unwrap()
+        .starttls();
+    let (_writer, reader) = connect_tls(format!("127.0.0.1:{}", port), ReconnectionSettings::DoNotReconnect, UTF_8, &tls).unwrap();
+    assert_eq!(next_message(&reader).code, ::code::Code::Notice);
+    assert_eq!(next_message(&reader).code, ::code::Code::RplStarttls);
+    assert_eq!(next_message(&reader).args[1], "Welcome, certified");
+}
@@ -0,0 +1,313 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use code::Code;
+use message::{Message, Prefix};
+
+// Delimits CTCP blocks.
+const DELIM: char = '\x01';
+// Low-level quoting character.
+const QUOTE: char = '\x10';
+
+/// Whether a CTCP message is a request or a reply.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CtcpKind {
+    /// Request, sent with a PRIVMSG.
+    Request,
+    /// Reply, sent with a NOTICE.
+    Reply,
+}
+
+/// A CTCP block, such as `ACTION waves` or `VERSION`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ctcp {
+    /// Command, uppercased.
+    pub command: String,
+    /// Parameters, everything after the first space.
+    pub params: Option<String>,
+}
+
+impl Ctcp {
+
+    /// Create a new CTCP block.
+    pub fn new<C: Into<String>>(command: C, params: Option<String>) -> Ctcp {
+        Ctcp {
+            command: command.into().to_uppercase(),
+            params,
+        }
+    }
+
+    /// Create an ACTION block, what `/me` sends.
+    pub fn action<S: Into<String>>(text: S) -> Ctcp {
+        Ctcp::new("ACTION", Some(text.into()))
+    }
+
+    /// Check if the text contains a CTCP block.
+    pub fn is_ctcp(text: &str) -> bool {
+        text.starts_with(DELIM)
+    }
+
+    /// Decode the CTCP blocks found in the text.
+    ///
+    /// Low-level quoting is undone, text outside of the blocks is ignored
+    /// and a missing final delimiter is tolerated.
+    pub fn decode(text: &str) -> Vec<Ctcp> {
+        let text = low_dequote(text);
+        let mut blocks = Vec::new();
+        let mut state = &text[..];
+
+        while let Some(start) = state.find(DELIM) {
+            let rest = &state[start + 1..];
+            let (raw, next) = match rest.find(DELIM) {
+                Some(end) => (&rest[..end], &rest[end + 1..]),
+                None => (rest, ""),
+            };
+            if !raw.is_empty() {
+                blocks.push(match raw.find(' ') {
+                    Some(idx) => Ctcp::new(&raw[..idx], Some(raw[idx + 1..].into())),
+                    None => Ctcp::new(raw, None),
+                });
+            }
+            state = next;
+        }
+
+        blocks
+    }
+
+    /// Decode the CTCP blocks of a PRIVMSG or NOTICE.
+    ///
+    /// `None` is returned if the message is not a CTCP message.
+    pub fn from_message(msg: &Message) -> Option<(CtcpKind, Vec<Ctcp>)> {
+        let kind = match msg.code {
+            Code::Privmsg => CtcpKind::Request,
+            Code::Notice => CtcpKind::Reply,
+            _ => return None,
+        };
+        let text = msg.args.get(1)?;
+        if !Ctcp::is_ctcp(text) {
+            return None;
+        }
+        let blocks = Ctcp::decode(text);
+        if blocks.is_empty() {
+            return None;
+        }
+        Some((kind, blocks))
+    }
+
+    /// Encode the block, with its delimiters and low-level quoting.
+    pub fn encode(&self) -> String {
+        let mut raw = self.command.clone();
+        if let Some(ref params) = self.params {
+            raw.push(' ');
+            raw.push_str(params);
+        }
+        format!("{}{}{}", DELIM, low_quote(&raw), DELIM)
+    }
+
+    /// Build a PRIVMSG sending this block as a request.
+    pub fn request(&self, target: &str) -> Message {
+        ctcp_message(Code::Privmsg, target, self.encode())
+    }
+
+    /// Build a NOTICE sending this block as a reply.
+    pub fn reply(&self, target: &str) -> Message {
+        ctcp_message(Code::Notice, target, self.encode())
+    }
+
+}
+
+fn ctcp_message(code: Code, target: &str, text: String) -> Message {
+    Message {
+        tags: Vec::new(),
+        prefix: None,
+        code,
+        args: vec![target.into(), text],
+        trailing: true,
+    }
+}
+
+fn low_quote(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\0' => res.push_str("\x100"),
+            '\n' => res.push_str("\x10n"),
+            '\r' => res.push_str("\x10r"),
+            QUOTE => res.push_str("\x10\x10"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+fn low_dequote(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != QUOTE {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => res.push('\0'),
+            Some('n') => res.push('\n'),
+            Some('r') => res.push('\r'),
+            Some(other) => res.push(other),
+            None => {}
+        }
+    }
+    res
+}
+
+/// Answers the usual CTCP requests.
+///
+/// Feed it the messages received from the server, and send the replies it produces.
+/// It answers VERSION, PING, TIME and CLIENTINFO requests sent by users.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CtcpResponder {
+    /// Reply to VERSION requests.
+    pub version: String,
+}
+
+impl CtcpResponder {
+
+    /// Create a new responder which answers VERSION requests with the given text.
+    pub fn new<S: Into<String>>(version: S) -> CtcpResponder {
+        CtcpResponder {
+            version: version.into(),
+        }
+    }
+
+    /// Build the replies for the given message.
+    ///
+    /// Messages which are not CTCP requests from a user produce no replies.
+    /// Each command is answered at most once per message, so a message packed
+    /// with requests cannot make the client flood itself off the server.
+    /// PING requests whose parameters contain control characters are ignored
+    /// rather than echoed.
+    pub fn respond(&self, msg: &Message) -> Vec<Message> {
+        let nickname = match msg.prefix {
+            Some(Prefix::User(ref user)) => &user.nickname,
+            _ => return Vec::new(),
+        };
+        let blocks = match Ctcp::from_message(msg) {
+            Some((CtcpKind::Request, blocks)) => blocks,
+            _ => return Vec::new(),
+        };
+
+        let mut answered: Vec<String> = Vec::new();
+        blocks.into_iter().filter_map(|block| {
+            if answered.contains(&block.command) {
+                return None;
+            }
+            let params = match &block.command[..] {
+                "VERSION" => Some(self.version.clone()),
+                "PING" => match block.params {
+                    Some(ref params) if params.chars().any(char::is_control) => return None,
+                    ref params => params.clone(),
+                },
+                "TIME" => Some(format_time(SystemTime::now())),
+                "CLIENTINFO" => Some("ACTION CLIENTINFO PING TIME VERSION".into()),
+                _ => return None,
+            };
+            answered.push(block.command.clone());
+            Some(Ctcp::new(block.command, params).reply(nickname))
+        }).collect()
+    }
+
+}
+
+// Format the time like "Sun, 18 Oct 2026 12:00:00 +0000", in UTC.
+fn format_time(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun",
+                                "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = secs / 86400;
+    let rem = secs % 86400;
+
+    // Convert the days since the epoch into a civil date.
+    let z = days as i64 + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+            DAYS[(days % 7) as usize], day, MONTHS[(month - 1) as usize], year,
+            rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+#[test]
+fn test_decode_action() {
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG #chan :\x01ACTION waves\x01").unwrap();
+    assert_eq!(Ctcp::from_message(&msg), Some((CtcpKind::Request, vec![Ctcp::action("waves")])));
+}
+
+#[test]
+fn test_decode_multiple() {
+    let blocks = Ctcp::decode("\x01VERSION\x01\x01ping 123\x01\x01TIME");
+    assert_eq!(blocks, vec![
+        Ctcp::new("VERSION", None),
+        Ctcp::new("PING", Some("123".into())),
+        Ctcp::new("TIME", None),
+    ]);
+}
+
+#[test]
+fn test_not_ctcp() {
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG #chan :hello").unwrap();
+    assert_eq!(Ctcp::from_message(&msg), None);
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG #chan :\x01\x01").unwrap();
+    assert_eq!(Ctcp::from_message(&msg), None);
+}
+
+#[test]
+fn test_quoting() {
+    let ctcp = Ctcp::new("DATA", Some("a\nb\x10c\0".into()));
+    let encoded = ctcp.encode();
+    assert_eq!(encoded, "\x01DATA a\x10nb\x10\x10c\x100\x01");
+    assert_eq!(Ctcp::decode(&encoded), vec![ctcp]);
+}
+
+#[test]
+fn test_encode_request() {
+    let msg = Ctcp::action("waves").request("#chan");
+    assert_eq!(msg.to_string(), "PRIVMSG #chan :\x01ACTION waves\x01");
+}
+
+#[test]
+fn test_responder() {
+    let responder = CtcpResponder::new("loirc");
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG me :\x01PING 123\x01\x01VERSION\x01\x01FOO\x01").unwrap();
+    let replies: Vec<String> = responder.respond(&msg).iter().map(|msg| msg.to_string()).collect();
+    assert_eq!(replies, vec!["NOTICE bob :\x01PING 123\x01", "NOTICE bob :\x01VERSION loirc\x01"]);
+
+    let msg = Message::parse(":bob!bob@bob.com NOTICE me :\x01VERSION other\x01").unwrap();
+    assert!(responder.respond(&msg).is_empty());
+}
+
+#[test]
+fn test_responder_flood() {
+    let responder = CtcpResponder::new("loirc");
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG me :\x01PING 1\x01\x01PING 2\x01\x01VERSION\x01\x01VERSION\x01").unwrap();
+    let replies: Vec<String> = responder.respond(&msg).iter().map(|msg| msg.to_string()).collect();
+    assert_eq!(replies, vec!["NOTICE bob :\x01PING 1\x01", "NOTICE bob :\x01VERSION loirc\x01"]);
+}
+
+#[test]
+fn test_responder_ping_control() {
+    let responder = CtcpResponder::new("loirc");
+    let msg = Message::parse(":bob!bob@bob.com PRIVMSG me :\x01PING a\x10nQUIT\x01").unwrap();
+    assert!(responder.respond(&msg).is_empty());
+}
+
+#[test]
+fn test_format_time() {
+    let time = UNIX_EPOCH + ::std::time::Duration::from_secs(1792324800);
+    assert_eq!(format_time(time), "Sun, 18 Oct 2026 12:00:00 +0000");
+}
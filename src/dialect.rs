@@ -0,0 +1,199 @@
+use code::{Code, CodeKind};
+use message::Message;
+
+/// Family of IRC server software, whose numerics differ from the specification.
+///
+/// It is detected from the version given by `RPL_YOURHOST` and `RPL_MYINFO`.
+/// `ServerInfo` does it automatically.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Dialect {
+    /// Unknown server, only the numerics from the specification are known.
+    #[default]
+    Generic,
+    /// Solanum, and its ancestors charybdis and ircd-seven.
+    Solanum,
+    /// InspIRCd
+    InspIRCd,
+    /// UnrealIRCd
+    UnrealIRCd,
+    /// ngIRCd
+    NgIRCd,
+}
+
+// Numeric, name and description.
+type Numeric = (u16, &'static str, &'static str);
+
+const SOLANUM: &[Numeric] = &[
+    (276, "RPL_WHOISCERTFP", "WHOIS TLS certificate fingerprint of the user"),
+    (320, "RPL_WHOISSPECIAL", "WHOIS special information about the user"),
+    (338, "RPL_WHOISACTUALLY", "WHOIS actual host of the user"),
+    (435, "ERR_BANNICKCHANGE", "Cannot change nickname while banned on channel"),
+    (477, "ERR_NEEDREGGEDNICK", "You need to be logged in to join or speak in the channel"),
+    (479, "ERR_BADCHANNAME", "Illegal channel name"),
+    (480, "ERR_THROTTLE", "Cannot join channel, throttled"),
+    (671, "RPL_WHOISSECURE", "WHOIS user is using a secure connection"),
+    (710, "RPL_KNOCK", "User knocked on the channel"),
+    (711, "RPL_KNOCKDLVR", "Your knock was delivered"),
+    (712, "ERR_TOOMANYKNOCK", "Too many knocks"),
+    (713, "ERR_CHANOPEN", "Channel is open"),
+    (714, "ERR_KNOCKONCHAN", "You are already on the channel"),
+    (716, "ERR_TARGUMODEG", "User only accepts messages from users they allowed"),
+    (717, "RPL_TARGNOTIFY", "User has been informed of your message"),
+    (718, "RPL_UMODEGMSG", "User is messaging you and you have caller ID enabled"),
+    (728, "RPL_QUIETLIST", "Quiet of the channel"),
+    (729, "RPL_ENDOFQUIETLIST", "End of quiet list"),
+];
+
+const INSPIRCD: &[Numeric] = &[
+    (276, "RPL_WHOISCERTFP", "WHOIS TLS certificate fingerprint of the user"),
+    (304, "RPL_SYNTAX", "Syntax of the command"),
+    (307, "RPL_WHOISREGNICK", "WHOIS user has a registered nickname"),
+    (320, "RPL_WHOISSPECIAL", "WHOIS special information about the user"),
+    (335, "RPL_WHOISBOT", "WHOIS user is a bot"),
+    (378, "RPL_WHOISHOST", "WHOIS host of the user"),
+    (379, "RPL_WHOISMODES", "WHOIS modes of the user"),
+    (671, "RPL_WHOISSECURE", "WHOIS user is using a secure connection"),
+];
+
+const UNREALIRCD: &[Numeric] = &[
+    (276, "RPL_WHOISCERTFP", "WHOIS TLS certificate fingerprint of the user"),
+    (307, "RPL_WHOISREGNICK", "WHOIS user has a registered nickname"),
+    (310, "RPL_WHOISHELPOP", "WHOIS user is available for help"),
+    (320, "RPL_WHOISSPECIAL", "WHOIS special information about the user"),
+    (335, "RPL_WHOISBOT", "WHOIS user is a bot"),
+    (378, "RPL_WHOISHOST", "WHOIS host of the user"),
+    (379, "RPL_WHOISMODES", "WHOIS modes of the user"),
+    (470, "ERR_LINKCHANNEL", "You were redirected to another channel"),
+    (477, "ERR_NEEDREGGEDNICK", "You need to be logged in to join the channel"),
+    (489, "ERR_SECUREONLYCHAN", "Cannot join channel, it requires a secure connection"),
+    (671, "RPL_WHOISSECURE", "WHOIS user is using a secure connection"),
+];
+
+const NGIRCD: &[Numeric] = &[
+    (307, "RPL_WHOISREGNICK", "WHOIS user has a registered nickname"),
+    (310, "RPL_WHOISSERVICE", "WHOIS user is a service"),
+    (335, "RPL_WHOISBOT", "WHOIS user is a bot"),
+    (378, "RPL_WHOISHOST", "WHOIS host of the user"),
+    (671, "RPL_WHOISSECURE", "WHOIS user is using a secure connection"),
+];
+
+impl Dialect {
+
+    /// Detect the dialect from the version of the server.
+    pub fn detect(version: &str) -> Dialect {
+        let version = version.to_ascii_lowercase();
+        let families = [
+            ("solanum", Dialect::Solanum),
+            ("charybdis", Dialect::Solanum),
+            ("ircd-seven", Dialect::Solanum),
+            ("inspircd", Dialect::InspIRCd),
+            ("unreal", Dialect::UnrealIRCd),
+            ("ngircd", Dialect::NgIRCd),
+        ];
+        for &(name, dialect) in families.iter() {
+            if version.contains(name) {
+                return dialect;
+            }
+        }
+        Dialect::Generic
+    }
+
+    /// Detect the dialect from a `RPL_YOURHOST` or `RPL_MYINFO` reply.
+    ///
+    /// Only the version is looked at, which `RPL_YOURHOST` gives after "running version".
+    /// `None` is returned for other messages, or when the version cannot be found.
+    pub fn from_message(msg: &Message) -> Option<Dialect> {
+        let version = match msg.code {
+            Code::RplYourhost => msg.args.last().and_then(|text| yourhost_version(text)),
+            Code::RplMyinfo => msg.args.get(2).map(|version| &version[..]),
+            _ => None,
+        };
+        version.map(Dialect::detect)
+    }
+
+    fn numerics(&self) -> &'static [Numeric] {
+        match *self {
+            Dialect::Generic => &[],
+            Dialect::Solanum => SOLANUM,
+            Dialect::InspIRCd => INSPIRCD,
+            Dialect::UnrealIRCd => UNREALIRCD,
+            Dialect::NgIRCd => NGIRCD,
+        }
+    }
+
+    fn find(&self, code: &Code) -> Option<&'static Numeric> {
+        let numeric = code.numeric()?;
+        self.numerics().iter().find(|entry| entry.0 == numeric)
+    }
+
+    /// Get the name of the code on servers of this dialect.
+    ///
+    /// Codes without a specific meaning fall back to `Code::name`.
+    pub fn name(&self, code: &Code) -> Option<&'static str> {
+        self.find(code).map(|entry| entry.1).or_else(|| code.name())
+    }
+
+    /// Get a short description of the code on servers of this dialect.
+    ///
+    /// Codes without a specific meaning fall back to `Code::description`.
+    pub fn description(&self, code: &Code) -> Option<&'static str> {
+        self.find(code).map(|entry| entry.2).or_else(|| code.description())
+    }
+
+    /// Get the kind of the code on servers of this dialect.
+    pub fn kind(&self, code: &Code) -> CodeKind {
+        match self.find(code) {
+            Some(entry) if entry.1.starts_with("ERR_") => CodeKind::Error,
+            Some(_) => CodeKind::Reply,
+            None => code.kind(),
+        }
+    }
+
+}
+
+// Find the version in "Your host is irc.example.net, running version ngircd-26.1".
+fn yourhost_version(text: &str) -> Option<&str> {
+    const MARKER: &str = "running version ";
+    let start = text.to_ascii_lowercase().find(MARKER)? + MARKER.len();
+    text[start..].split_whitespace().next()
+}
+
+#[test]
+fn test_detect() {
+    assert_eq!(Dialect::detect("solanum-1.0-dev"), Dialect::Solanum);
+    assert_eq!(Dialect::detect("InspIRCd-3"), Dialect::InspIRCd);
+    assert_eq!(Dialect::detect("UnrealIRCd-6.1.0"), Dialect::UnrealIRCd);
+    assert_eq!(Dialect::detect("ngircd-26.1"), Dialect::NgIRCd);
+    assert_eq!(Dialect::detect("ircd-hybrid-8.2"), Dialect::Generic);
+
+    let msg = Message::parse(":irc.example.net 004 bob irc.example.net charybdis-4.1.2 DQRSZagiloswz CFILPQbcefgijklmnopqrstvz").unwrap();
+    assert_eq!(Dialect::from_message(&msg), Some(Dialect::Solanum));
+    let msg = Message::parse(":irc.example.net 002 bob :Your host is irc.example.net, running version ngircd-26.1").unwrap();
+    assert_eq!(Dialect::from_message(&msg), Some(Dialect::NgIRCd));
+    let msg = Message::parse(":irc.example.net 001 bob :Welcome").unwrap();
+    assert_eq!(Dialect::from_message(&msg), None);
+}
+
+#[test]
+fn test_detect_misleading_host() {
+    let msg = Message::parse(":unreal.example.net 002 bob :Your host is unreal.example.net, running version solanum-1.0-dev").unwrap();
+    assert_eq!(Dialect::from_message(&msg), Some(Dialect::Solanum));
+    let msg = Message::parse(":unreal.example.net 002 bob :Your host is unreal.example.net[10.0.0.1/6667], running version ircd-hybrid-8.2").unwrap();
+    assert_eq!(Dialect::from_message(&msg), Some(Dialect::Generic));
+    let msg = Message::parse(":unreal.example.net 002 bob :Your host is unreal.example.net").unwrap();
+    assert_eq!(Dialect::from_message(&msg), None);
+}
+
+#[test]
+fn test_numerics() {
+    let code = Code::from_numeric(477);
+    assert_eq!(Dialect::Generic.name(&code), Some("ERR_NOCHANMODES"));
+    assert_eq!(Dialect::Solanum.name(&code), Some("ERR_NEEDREGGEDNICK"));
+    let code = Code::from_numeric(310);
+    assert_eq!(Dialect::Generic.name(&code), None);
+    assert_eq!(Dialect::UnrealIRCd.name(&code), Some("RPL_WHOISHELPOP"));
+    assert_eq!(Dialect::NgIRCd.name(&code), Some("RPL_WHOISSERVICE"));
+    assert_eq!(Dialect::Solanum.kind(&Code::from_numeric(712)), CodeKind::Error);
+    assert_eq!(Dialect::Generic.kind(&Code::from_numeric(712)), CodeKind::Reply);
+    assert_eq!(Dialect::InspIRCd.name(&Code::Privmsg), Some("PRIVMSG"));
+}
@@ -0,0 +1,498 @@
+use std::fmt::{self, Write};
+
+const BOLD: char = '\x02';
+const COLOR: char = '\x03';
+const HEX_COLOR: char = '\x04';
+const RESET: char = '\x0f';
+const MONOSPACE: char = '\x11';
+const REVERSE: char = '\x16';
+const ITALIC: char = '\x1d';
+const STRIKETHROUGH: char = '\x1e';
+const UNDERLINE: char = '\x1f';
+
+// Palette index which means the default color.
+const DEFAULT_COLOR: u8 = 99;
+
+// Closest color of the 256 color ANSI palette for each mIRC color.
+const ANSI_COLORS: [u8; 99] = [
+    15, 0, 4, 2, 9, 1, 5, 3, 11, 10, 6, 14, 12, 13, 8, 7,
+    52, 94, 100, 58, 22, 29, 23, 24, 17, 54, 53, 89,
+    88, 130, 142, 64, 28, 35, 30, 25, 18, 91, 90, 125,
+    124, 166, 184, 106, 34, 49, 37, 33, 19, 129, 127, 161,
+    196, 208, 226, 154, 46, 86, 51, 75, 21, 171, 201, 198,
+    203, 215, 227, 191, 83, 122, 87, 111, 63, 177, 207, 205,
+    217, 223, 229, 193, 157, 158, 159, 153, 147, 183, 219, 212,
+    16, 233, 235, 237, 239, 241, 244, 247, 250, 254, 231,
+];
+
+/// Color of some formatted text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    /// Color of the mIRC palette, from 0 to 98, sent with `\x03`.
+    Palette(u8),
+    /// Red, green and blue color, sent with `\x04`.
+    Rgb(u8, u8, u8),
+}
+
+/// Style of some formatted text.
+///
+/// The builder methods make it easy to create one, `Style::new().bold().fg(Color::Palette(4))`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Style {
+    /// Bold
+    pub bold: bool,
+    /// Italic
+    pub italic: bool,
+    /// Underline
+    pub underline: bool,
+    /// Strikethrough
+    pub strikethrough: bool,
+    /// Monospace
+    pub monospace: bool,
+    /// Reverse, swaps the foreground and background colors.
+    pub reverse: bool,
+    /// Foreground color, `None` for the default.
+    pub fg: Option<Color>,
+    /// Background color, `None` for the default.
+    pub bg: Option<Color>,
+}
+
+impl Style {
+
+    /// Create a plain style.
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    /// Enable bold.
+    pub fn bold(mut self) -> Style {
+        self.bold = true;
+        self
+    }
+
+    /// Enable italic.
+    pub fn italic(mut self) -> Style {
+        self.italic = true;
+        self
+    }
+
+    /// Enable underline.
+    pub fn underline(mut self) -> Style {
+        self.underline = true;
+        self
+    }
+
+    /// Enable strikethrough.
+    pub fn strikethrough(mut self) -> Style {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Enable monospace.
+    pub fn monospace(mut self) -> Style {
+        self.monospace = true;
+        self
+    }
+
+    /// Enable reverse.
+    pub fn reverse(mut self) -> Style {
+        self.reverse = true;
+        self
+    }
+
+    /// Set the foreground color.
+    pub fn fg(mut self, color: Color) -> Style {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Set the background color.
+    pub fn bg(mut self, color: Color) -> Style {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Check if this is the plain style.
+    pub fn is_plain(&self) -> bool {
+        *self == Style::default()
+    }
+
+    // Check if going from self to other requires turning something off.
+    fn removes(&self, other: &Style) -> bool {
+        (self.bold && !other.bold) || (self.italic && !other.italic) ||
+        (self.underline && !other.underline) || (self.strikethrough && !other.strikethrough) ||
+        (self.monospace && !other.monospace) || (self.reverse && !other.reverse) ||
+        (self.fg.is_some() && other.fg.is_none()) || (self.bg.is_some() && other.bg.is_none())
+    }
+
+    /// Write the control codes which change the style from `self` to `next`.
+    pub fn write_transition<W: Write>(&self, next: &Style, out: &mut W) -> fmt::Result {
+        let from = if self.removes(next) {
+            out.write_char(RESET)?;
+            Style::default()
+        } else {
+            *self
+        };
+
+        let toggles = [(from.bold, next.bold, BOLD),
+                       (from.italic, next.italic, ITALIC),
+                       (from.underline, next.underline, UNDERLINE),
+                       (from.strikethrough, next.strikethrough, STRIKETHROUGH),
+                       (from.monospace, next.monospace, MONOSPACE),
+                       (from.reverse, next.reverse, REVERSE)];
+        for &(before, after, code) in toggles.iter() {
+            if before != after {
+                out.write_char(code)?;
+            }
+        }
+
+        if from.fg != next.fg || from.bg != next.bg {
+            write_colors(next.fg, next.bg, out)?;
+        }
+        Ok(())
+    }
+
+    // Write the transition before a span of text. A comma right after a color
+    // code could be read as a background, so it is guarded.
+    pub(crate) fn write_span_start<W: Write>(&self, next: &Style, text: &str, out: &mut W) -> fmt::Result {
+        self.write_transition(next, out)?;
+        let colored = next.fg.is_some() || next.bg.is_some();
+        let recolored = self.fg != next.fg || self.bg != next.bg || self.removes(next);
+        if colored && recolored && text.starts_with(',') {
+            out.write_char(BOLD)?;
+            out.write_char(BOLD)?;
+        }
+        Ok(())
+    }
+
+    fn write_ansi<W: Write>(&self, out: &mut W) -> fmt::Result {
+        out.write_str("\x1b[0")?;
+        let attrs = [(self.bold, "1"), (self.italic, "3"), (self.underline, "4"),
+                     (self.reverse, "7"), (self.strikethrough, "9")];
+        for &(enabled, code) in attrs.iter() {
+            if enabled {
+                write!(out, ";{}", code)?;
+            }
+        }
+        if let Some(fg) = self.fg {
+            write_ansi_color(fg, 38, out)?;
+        }
+        if let Some(bg) = self.bg {
+            write_ansi_color(bg, 48, out)?;
+        }
+        out.write_char('m')
+    }
+
+}
+
+fn write_palette<W: Write>(fg: u8, bg: Option<u8>, out: &mut W) -> fmt::Result {
+    write!(out, "{}{:02}", COLOR, fg)?;
+    match bg {
+        Some(bg) => write!(out, ",{:02}", bg),
+        None => Ok(()),
+    }
+}
+
+fn write_hex<W: Write>(fg: (u8, u8, u8), bg: Option<(u8, u8, u8)>, out: &mut W) -> fmt::Result {
+    write!(out, "{}{:02X}{:02X}{:02X}", HEX_COLOR, fg.0, fg.1, fg.2)?;
+    match bg {
+        Some(bg) => write!(out, ",{:02X}{:02X}{:02X}", bg.0, bg.1, bg.2),
+        None => Ok(()),
+    }
+}
+
+fn write_colors<W: Write>(fg: Option<Color>, bg: Option<Color>, out: &mut W) -> fmt::Result {
+    match (fg, bg) {
+        (None, None) => Ok(()),
+        (Some(Color::Palette(fg)), None) => write_palette(fg, None, out),
+        (Some(Color::Rgb(r, g, b)), None) => write_hex((r, g, b), None, out),
+        (Some(Color::Palette(fg)), Some(Color::Palette(bg))) => write_palette(fg, Some(bg), out),
+        (Some(Color::Rgb(r, g, b)), Some(Color::Rgb(br, bgr, bb))) => write_hex((r, g, b), Some((br, bgr, bb)), out),
+        // A code can only set the background along with a foreground. Set the
+        // background with a placeholder foreground, then the real foreground.
+        (fg, Some(Color::Palette(bg))) => {
+            write_palette(DEFAULT_COLOR, Some(bg), out)?;
+            match fg {
+                Some(Color::Rgb(r, g, b)) => write_hex((r, g, b), None, out),
+                _ => Ok(()),
+            }
+        }
+        (fg, Some(Color::Rgb(br, bgr, bb))) => {
+            write_hex((0, 0, 0), Some((br, bgr, bb)), out)?;
+            match fg {
+                Some(Color::Palette(fg)) => write_palette(fg, None, out),
+                _ => write_palette(DEFAULT_COLOR, None, out),
+            }
+        }
+    }
+}
+
+fn write_ansi_color<W: Write>(color: Color, base: u8, out: &mut W) -> fmt::Result {
+    match color {
+        Color::Palette(idx) => match ANSI_COLORS.get(idx as usize) {
+            Some(ansi) => write!(out, ";{};5;{}", base, ansi),
+            None => Ok(()),
+        },
+        Color::Rgb(r, g, b) => write!(out, ";{};2;{};{};{}", base, r, g, b),
+    }
+}
+
+/// Piece of text with a single style.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Span {
+    /// Style of the text.
+    pub style: Style,
+    /// Text, without control codes.
+    pub text: String,
+}
+
+/// Text with mIRC formatting, as a list of styled spans.
+///
+/// `parse` reads the control codes found in message text, and the
+/// `Display` implementation writes them back. Text can also be built
+/// programmatically:
+///
+/// ```
+/// use loirc::{Color, FormattedText, Style};
+///
+/// let text = FormattedText::new().text("build ").push(Style::new().bold().fg(Color::Palette(3)), "passed");
+/// assert_eq!(text.to_string(), "build \x02\x0303passed");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FormattedText {
+    /// Spans of text, in order.
+    pub spans: Vec<Span>,
+}
+
+impl FormattedText {
+
+    /// Create an empty text.
+    pub fn new() -> FormattedText {
+        FormattedText::default()
+    }
+
+    /// Parse the formatting control codes of the text.
+    pub fn parse(text: &str) -> FormattedText {
+        let mut res = FormattedText::new();
+        let mut style = Style::default();
+        let mut current = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let mut next = style;
+            match c {
+                BOLD => next.bold = !next.bold,
+                ITALIC => next.italic = !next.italic,
+                UNDERLINE => next.underline = !next.underline,
+                STRIKETHROUGH => next.strikethrough = !next.strikethrough,
+                MONOSPACE => next.monospace = !next.monospace,
+                REVERSE => next.reverse = !next.reverse,
+                RESET => next = Style::default(),
+                COLOR => {
+                    match read_palette(&mut chars) {
+                        None => {
+                            next.fg = None;
+                            next.bg = None;
+                        }
+                        Some(fg) => {
+                            next.fg = fg;
+                            if chars.peek() == Some(&',') {
+                                let mut ahead = chars.clone();
+                                ahead.next();
+                                if let Some(bg) = read_palette(&mut ahead) {
+                                    next.bg = bg;
+                                    chars = ahead;
+                                }
+                            }
+                        }
+                    }
+                }
+                HEX_COLOR => {
+                    match read_hex(&mut chars) {
+                        None => {
+                            next.fg = None;
+                            next.bg = None;
+                        }
+                        Some(fg) => {
+                            next.fg = Some(fg);
+                            if chars.peek() == Some(&',') {
+                                let mut ahead = chars.clone();
+                                ahead.next();
+                                if let Some(bg) = read_hex(&mut ahead) {
+                                    next.bg = Some(bg);
+                                    chars = ahead;
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    current.push(c);
+                    continue;
+                }
+            }
+            if next != style {
+                res.push_span(style, &mut current);
+                style = next;
+            }
+        }
+
+        res.push_span(style, &mut current);
+        res
+    }
+
+    /// Remove the formatting control codes from the text.
+    pub fn strip(text: &str) -> String {
+        FormattedText::parse(text).to_plain()
+    }
+
+    // Add the text as a span, merging it with the last span if it has the same style.
+    fn push_span(&mut self, style: Style, text: &mut String) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(last) = self.spans.last_mut() {
+            if last.style == style {
+                last.text.push_str(text);
+                text.clear();
+                return;
+            }
+        }
+        self.spans.push(Span {
+            style,
+            text: ::std::mem::take(text),
+        });
+    }
+
+    /// Append text with the given style.
+    pub fn push<S: Into<String>>(mut self, style: Style, text: S) -> FormattedText {
+        self.push_span(style, &mut text.into());
+        self
+    }
+
+    /// Append text without any style.
+    pub fn text<S: Into<String>>(self, text: S) -> FormattedText {
+        self.push(Style::default(), text)
+    }
+
+    /// Get the text without any formatting.
+    pub fn to_plain(&self) -> String {
+        self.spans.iter().map(|span| &span.text[..]).collect()
+    }
+
+    /// Render the text with ANSI escape codes, for terminals.
+    pub fn to_ansi(&self) -> String {
+        let mut res = String::new();
+        let mut style = Style::default();
+        for span in self.spans.iter() {
+            if span.style != style {
+                // Writing to a String cannot fail.
+                let _ = span.style.write_ansi(&mut res);
+                style = span.style;
+            }
+            res.push_str(&span.text);
+        }
+        if !style.is_plain() {
+            res.push_str("\x1b[0m");
+        }
+        res
+    }
+
+}
+
+/// Write the text with mIRC control codes.
+impl fmt::Display for FormattedText {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut style = Style::default();
+        for span in self.spans.iter() {
+            style.write_span_start(&span.style, &span.text, f)?;
+            f.write_str(&span.text)?;
+            style = span.style;
+        }
+        Ok(())
+    }
+
+}
+
+// Read up to two digits of a palette color. Some(None) is the default color.
+fn read_palette<I: Iterator<Item = char> + Clone>(chars: &mut ::std::iter::Peekable<I>) -> Option<Option<Color>> {
+    let mut value: Option<u8> = None;
+    for _ in 0..2 {
+        match chars.peek().and_then(|c| c.to_digit(10)) {
+            Some(digit) => {
+                value = Some(value.unwrap_or(0) * 10 + digit as u8);
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    value.map(|value| if value == DEFAULT_COLOR { None } else { Some(Color::Palette(value)) })
+}
+
+// Read exactly six hexadecimal digits.
+fn read_hex<I: Iterator<Item = char> + Clone>(chars: &mut ::std::iter::Peekable<I>) -> Option<Color> {
+    let mut ahead = chars.clone();
+    let mut value = 0u32;
+    for _ in 0..6 {
+        value = value * 16 + ahead.next()?.to_digit(16)?;
+    }
+    *chars = ahead;
+    Some(Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+#[test]
+fn test_parse() {
+    let text = FormattedText::parse("a\x02b\x1dc\x0fd");
+    assert_eq!(text.spans, vec![
+        Span { style: Style::new(), text: "a".into() },
+        Span { style: Style::new().bold(), text: "b".into() },
+        Span { style: Style::new().bold().italic(), text: "c".into() },
+        Span { style: Style::new(), text: "d".into() },
+    ]);
+}
+
+#[test]
+fn test_parse_colors() {
+    let text = FormattedText::parse("\x034red\x0304,12on blue\x03,x\x0399,1y");
+    assert_eq!(text.spans, vec![
+        Span { style: Style::new().fg(Color::Palette(4)), text: "red".into() },
+        Span { style: Style::new().fg(Color::Palette(4)).bg(Color::Palette(12)), text: "on blue".into() },
+        Span { style: Style::new(), text: ",x".into() },
+        Span { style: Style::new().bg(Color::Palette(1)), text: "y".into() },
+    ]);
+}
+
+#[test]
+fn test_parse_hex_colors() {
+    let text = FormattedText::parse("\x04FF0000red\x04ff0000,0000FFon blue\x04zz");
+    assert_eq!(text.spans, vec![
+        Span { style: Style::new().fg(Color::Rgb(255, 0, 0)), text: "red".into() },
+        Span { style: Style::new().fg(Color::Rgb(255, 0, 0)).bg(Color::Rgb(0, 0, 255)), text: "on blue".into() },
+        Span { style: Style::new(), text: "zz".into() },
+    ]);
+}
+
+#[test]
+fn test_strip() {
+    assert_eq!(FormattedText::strip("\x02bold\x02 \x0304,01red\x03 \x1funder\x0f"), "bold red under");
+}
+
+#[test]
+fn test_round_trip() {
+    let text = FormattedText::new()
+        .text("plain ")
+        .push(Style::new().bold(), "bold ")
+        .push(Style::new().italic().fg(Color::Palette(4)), "5 red")
+        .push(Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Palette(2)), "mixed")
+        .push(Style::new().bg(Color::Rgb(4, 5, 6)), ",bg only")
+        .text(" end");
+    let line = text.to_string();
+    assert_eq!(FormattedText::parse(&line), text);
+}
+
+#[test]
+fn test_ansi() {
+    let text = FormattedText::new().text("a").push(Style::new().bold().fg(Color::Palette(4)), "b");
+    assert_eq!(text.to_ansi(), "a\x1b[0;1;38;5;9mb\x1b[0m");
+}
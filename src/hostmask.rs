@@ -0,0 +1,291 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use casemap::CaseMapping;
+use message::PrefixUser;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token {
+    // Matches any sequence, '*'.
+    Many,
+    // Matches a single character, '?'.
+    One,
+    // Matches this character.
+    Char(char),
+}
+
+fn tokenize(mask: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = mask.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' => Token::Many,
+            '?' => Token::One,
+            '\\' => match chars.next() {
+                Some(escaped) => Token::Char(escaped),
+                None => Token::Char('\\'),
+            },
+            _ => Token::Char(c),
+        });
+    }
+    tokens
+}
+
+fn glob(tokens: &[Token], text: &[char], mapping: CaseMapping) -> bool {
+    let (mut t, mut c) = (0, 0);
+    // Position of the last '*' and of the text when it was seen, to backtrack.
+    let mut star: Option<(usize, usize)> = None;
+
+    while c < text.len() {
+        match tokens.get(t) {
+            Some(&Token::Many) => {
+                star = Some((t, c));
+                t += 1;
+                continue;
+            }
+            Some(&Token::One) => {
+                t += 1;
+                c += 1;
+                continue;
+            }
+            Some(&Token::Char(expected)) if mapping.to_lower_char(expected) == mapping.to_lower_char(text[c]) => {
+                t += 1;
+                c += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match star {
+            Some((star_t, star_c)) => {
+                // Let the last '*' swallow one more character.
+                t = star_t + 1;
+                c = star_c + 1;
+                star = Some((star_t, star_c + 1));
+            }
+            None => return false,
+        }
+    }
+
+    tokens[t..].iter().all(|token| *token == Token::Many)
+}
+
+/// Wildcard mask matching `nick!user@host` strings, such as ban masks.
+///
+/// `*` matches any sequence of characters, `?` matches a single character,
+/// and `\` escapes the next character.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Hostmask {
+    mask: String,
+    // The mask used for local matching. Ban masks keep the values of the user
+    // as they are in `mask`, and escape them here.
+    pattern: String,
+}
+
+impl Hostmask {
+
+    /// Create a mask, completing it like servers do.
+    ///
+    /// `nick` becomes `nick!*@*`, `nick!user` becomes `nick!user@*`,
+    /// and `user@host` becomes `*!user@host`.
+    pub fn new(mask: &str) -> Hostmask {
+        let mask = match (mask.find('!'), mask.find('@')) {
+            (Some(_), Some(_)) => mask.to_string(),
+            (Some(_), None) => format!("{}@*", mask),
+            (None, Some(_)) => format!("*!{}", mask),
+            (None, None) => format!("{}!*@*", mask),
+        };
+        Hostmask {
+            pattern: mask.clone(),
+            mask,
+        }
+    }
+
+    /// Get the mask.
+    pub fn as_str(&self) -> &str {
+        &self.mask
+    }
+
+    /// Check if the mask matches the given text, using the case mapping.
+    pub fn matches(&self, text: &str, mapping: CaseMapping) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        glob(&tokenize(&self.pattern), &text, mapping)
+    }
+
+    /// Check if the mask matches the user, using the case mapping.
+    ///
+    /// Missing parts of the user's prefix are considered empty.
+    pub fn matches_user(&self, user: &PrefixUser, mapping: CaseMapping) -> bool {
+        let text = format!("{}!{}@{}",
+                           user.nickname,
+                           user.username.as_ref().map_or("", |s| &s[..]),
+                           user.hostname.as_ref().map_or("", |s| &s[..]));
+        self.matches(&text, mapping)
+    }
+
+}
+
+impl fmt::Display for Hostmask {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.mask)
+    }
+
+}
+
+/// Style of ban mask generated from a user.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BanMaskStyle {
+    /// `nick!*@*`
+    Nick,
+    /// `*!*@host`
+    Host,
+    /// `*!user@host`
+    UserHost,
+    /// `*!*@*.domain`, or `*!*@1.2.3.*` for IPv4 addresses.
+    Domain,
+    /// `*!user@*.domain`, or `*!user@1.2.3.*` for IPv4 addresses.
+    UserDomain,
+    /// `nick!user@host`
+    Full,
+}
+
+// Escape the wildcard characters of a literal value.
+fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '*' || c == '?' || c == '\\' {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+// Keep a literal value as it is, servers do not support escaping.
+fn literal(text: &str) -> String {
+    text.into()
+}
+
+// Unidented usernames, starting with '~', are matched regardless of the '~'.
+fn user_part(username: Option<&str>, quote: fn(&str) -> String) -> String {
+    match username {
+        Some(username) if username.starts_with('~') => format!("*{}", quote(&username[1..])),
+        Some(username) => quote(username),
+        None => "*".into(),
+    }
+}
+
+fn domain_part(hostname: &str, quote: fn(&str) -> String) -> String {
+    if let Ok(addr) = hostname.parse::<Ipv4Addr>() {
+        let octets = addr.octets();
+        return format!("{}.{}.{}.*", octets[0], octets[1], octets[2]);
+    }
+    // Cloaks such as "user/bob" and IPv6 addresses identify a single user,
+    // and hosts with a single label have no domain.
+    if hostname.contains('/') || hostname.contains(':') {
+        return quote(hostname);
+    }
+    match hostname.find('.') {
+        Some(idx) if hostname[idx + 1..].contains('.') => format!("*{}", quote(&hostname[idx..])),
+        _ => quote(hostname),
+    }
+}
+
+impl PrefixUser {
+
+    /// Generate a ban mask of the given style for this user.
+    ///
+    /// Missing parts of the user's prefix are replaced by `*`. The values of the
+    /// user are kept as they are in the mask sent to the server, and are matched
+    /// literally by `Hostmask::matches`.
+    pub fn ban_mask(&self, style: BanMaskStyle) -> Hostmask {
+        Hostmask {
+            mask: self.format_mask(style, literal),
+            pattern: self.format_mask(style, escape),
+        }
+    }
+
+    fn format_mask(&self, style: BanMaskStyle, quote: fn(&str) -> String) -> String {
+        let username = self.username.as_ref().map(|s| &s[..]);
+        let host = match self.hostname {
+            Some(ref hostname) => match style {
+                BanMaskStyle::Domain | BanMaskStyle::UserDomain => domain_part(hostname, quote),
+                _ => quote(hostname),
+            },
+            None => "*".into(),
+        };
+        match style {
+            BanMaskStyle::Nick => format!("{}!*@*", quote(&self.nickname)),
+            BanMaskStyle::Host | BanMaskStyle::Domain => format!("*!*@{}", host),
+            BanMaskStyle::UserHost | BanMaskStyle::UserDomain => format!("*!{}@{}", user_part(username, quote), host),
+            BanMaskStyle::Full => format!("{}!{}@{}", quote(&self.nickname), user_part(username, quote), host),
+        }
+    }
+
+}
+
+#[test]
+fn test_matches() {
+    let mask = Hostmask::new("*!*@*.example.com");
+    assert!(mask.matches("bob!bob@host.example.com", CaseMapping::Rfc1459));
+    assert!(mask.matches("bob!bob@a.b.EXAMPLE.com", CaseMapping::Rfc1459));
+    assert!(!mask.matches("bob!bob@example.com", CaseMapping::Rfc1459));
+    assert!(Hostmask::new("b?b").matches("BOB!x@y", CaseMapping::Rfc1459));
+    assert!(Hostmask::new("nick[1]").matches("nick{1}!x@y", CaseMapping::Rfc1459));
+    assert!(!Hostmask::new("nick[1]").matches("nick{1}!x@y", CaseMapping::Ascii));
+}
+
+#[test]
+fn test_escape() {
+    let mask = Hostmask::new("a\\*b!*@*");
+    assert!(mask.matches("a*b!x@y", CaseMapping::Ascii));
+    assert!(!mask.matches("axb!x@y", CaseMapping::Ascii));
+}
+
+#[test]
+fn test_completion() {
+    assert_eq!(Hostmask::new("bob").as_str(), "bob!*@*");
+    assert_eq!(Hostmask::new("bob!bob").as_str(), "bob!bob@*");
+    assert_eq!(Hostmask::new("bob@host").as_str(), "*!bob@host");
+}
+
+#[test]
+fn test_matches_user() {
+    let user = PrefixUser::new("bob", "~bob", "host.example.com");
+    assert!(Hostmask::new("*!*bob@*").matches_user(&user, CaseMapping::Rfc1459));
+    assert!(!Hostmask::new("alice").matches_user(&user, CaseMapping::Rfc1459));
+}
+
+#[test]
+fn test_ban_mask() {
+    let user = PrefixUser::new("bob", "~bob", "host.example.com");
+    assert_eq!(user.ban_mask(BanMaskStyle::Nick).as_str(), "bob!*@*");
+    assert_eq!(user.ban_mask(BanMaskStyle::Host).as_str(), "*!*@host.example.com");
+    assert_eq!(user.ban_mask(BanMaskStyle::UserHost).as_str(), "*!*bob@host.example.com");
+    assert_eq!(user.ban_mask(BanMaskStyle::Domain).as_str(), "*!*@*.example.com");
+    assert_eq!(user.ban_mask(BanMaskStyle::UserDomain).as_str(), "*!*bob@*.example.com");
+    assert_eq!(user.ban_mask(BanMaskStyle::Full).as_str(), "bob!*bob@host.example.com");
+    for style in [BanMaskStyle::Nick, BanMaskStyle::Host, BanMaskStyle::Domain, BanMaskStyle::Full].iter() {
+        assert!(user.ban_mask(*style).matches_user(&user, CaseMapping::Rfc1459));
+    }
+}
+
+#[test]
+fn test_ban_mask_hosts() {
+    let user = PrefixUser::new("bob", "bob", "192.168.1.20");
+    assert_eq!(user.ban_mask(BanMaskStyle::Domain).as_str(), "*!*@192.168.1.*");
+    let user = PrefixUser::new("bob", "bob", "user/bob");
+    assert_eq!(user.ban_mask(BanMaskStyle::Domain).as_str(), "*!*@user/bob");
+    let user = PrefixUser::new("bob", "bob", "example.com");
+    assert_eq!(user.ban_mask(BanMaskStyle::Domain).as_str(), "*!*@example.com");
+}
+
+#[test]
+fn test_ban_mask_not_escaped() {
+    let user = PrefixUser::new("bo\\b", "b?b", "host");
+    let mask = user.ban_mask(BanMaskStyle::Full);
+    assert_eq!(mask.as_str(), "bo\\b!b?b@host");
+    assert_eq!(mask.to_string(), "bo\\b!b?b@host");
+    assert!(mask.matches_user(&user, CaseMapping::Ascii));
+    assert!(!mask.matches_user(&PrefixUser::new("bo\\b", "bob", "host"), CaseMapping::Ascii));
+}
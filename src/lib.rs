@@ -33,15 +33,76 @@
 //!     }
 //! }
 //! ```
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for `Message`, `Prefix`,
+//! `Code` and `Event`, among others.
+//!
+//! The `tls` feature adds `connect_tls`, to connect with TLS or upgrade the connection
+//! with `STARTTLS`. See `TlsSettings`.
 #![deny(missing_docs)]
 extern crate encoding;
+extern crate unicode_segmentation;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(feature = "tls")]
+extern crate ring;
+#[cfg(feature = "tls")]
+extern crate rustls;
+#[cfg(feature = "tls")]
+extern crate webpki_roots;
+#[cfg(all(test, feature = "tls"))]
+extern crate rcgen;
 
 mod activity_monitor;
+mod backoff;
+mod builder;
+mod casemap;
+mod charset;
 mod code;
+mod command;
 mod connection;
+mod ctcp;
+mod dialect;
+mod formatting;
+mod hostmask;
 mod message;
+mod mode;
+mod proxy;
+mod reply;
+mod server_info;
+mod servers;
+mod split;
+#[cfg(feature = "tls")]
+mod tls;
+mod transport;
 
 pub use activity_monitor::{ActivityMonitor, MonitorSettings};
-pub use connection::{connect, Event, Error, Reader, ReconnectionSettings, Writer};
-pub use code::Code;
-pub use message::{ParseError, Message, Prefix, PrefixUser};
+pub use backoff::{Backoff, BackoffPolicy};
+pub use builder::{BuildError, MessageBuilder};
+pub use casemap::{CaseMapping, IrcKey};
+pub use charset::{DecodePolicy, Decoded, EncodePolicy, LineError};
+pub use connection::{connect, connect_servers, connect_transport, connect_transport_with_filter, connect_with_policies, Event, Error,
+                     Reader, ReconnectionSettings, Writer};
+pub use code::{Code, CodeKind};
+pub use command::{Command, DecodeError};
+pub use ctcp::{Ctcp, CtcpKind, CtcpResponder};
+pub use dialect::Dialect;
+pub use formatting::{Color, FormattedText, Span, Style};
+pub use hostmask::{BanMaskStyle, Hostmask};
+pub use message::{Args, Message, MessageRef, ParseError, Prefix, PrefixRef, PrefixUser, PrefixUserRef, Tag,
+                  TagRef, Tags};
+pub use mode::{ChannelModes, ModeChange, ModeClass};
+pub use proxy::Proxy;
+pub use server_info::ServerInfo;
+pub use servers::{Server, ServerList};
+pub use transport::{Connection, TcpTransport, Transport, TransportWriter};
+pub use split::Splitter;
+#[cfg(feature = "tls")]
+pub use connection::{connect_tls, connect_tls_with_policies};
+#[cfg(feature = "tls")]
+pub use tls::TlsSettings;
+pub use reply::{ChannelType, NamesNick, RplBanList, RplChannelModeIs, RplListReply, RplNamReply, RplTopic,
+                RplTopicWhoTime, RplWelcome, RplWhoReply, RplWhoisUser};
@@ -1,7 +1,12 @@
+use std::error;
+use std::fmt;
+use std::str;
+
 use code::Code;
 
 /// Error generated by the parser.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ParseError {
     /// String was empty.
     EmptyCommand,
@@ -9,17 +14,87 @@ pub enum ParseError {
     EmptyMessage,
     /// Unexpected end of the string.
     UnexpectedEnd,
+    /// The tag section was present but did not contain any tag.
+    /// The value is the byte offset of the `@`.
+    EmptyTags(usize),
+    /// A tag had an empty key. The value is the byte offset of the tag.
+    EmptyTagKey(usize),
+    /// A tag key contained characters that are not allowed.
+    /// The value is the byte offset of the key.
+    InvalidTagKey(usize),
+    /// Strict mode: the line is longer than allowed. The value is the byte offset
+    /// where the limit was exceeded.
+    LineTooLong(usize),
+    /// Strict mode: the line has more than 15 parameters. The value is the byte offset
+    /// of the first extra parameter.
+    TooManyParams(usize),
+    /// Strict mode: the line contains a NUL byte. The value is its byte offset.
+    NulByte(usize),
+    /// Strict mode: the line contains a CR or LF which does not end the line.
+    /// The value is its byte offset.
+    BareLineBreak(usize),
+    /// Strict mode: the prefix is empty or contains an invalid character.
+    /// The value is the byte offset of the problem.
+    InvalidPrefix(usize),
+    /// Strict mode: the command is neither a word nor a three digit numeric.
+    /// The value is the byte offset of the command.
+    InvalidCommand(usize),
+    /// Strict mode: the line ends after the tags or the prefix, where the
+    /// command was expected. The value is the byte offset of the end.
+    MissingCommand(usize),
+}
+
+impl fmt::Display for ParseError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::EmptyCommand => f.write_str("empty command"),
+            ParseError::EmptyMessage => f.write_str("empty message"),
+            ParseError::UnexpectedEnd => f.write_str("unexpected end of message"),
+            ParseError::EmptyTags(pos) => write!(f, "empty tag section at byte {}", pos),
+            ParseError::EmptyTagKey(pos) => write!(f, "empty tag key at byte {}", pos),
+            ParseError::InvalidTagKey(pos) => write!(f, "invalid tag key at byte {}", pos),
+            ParseError::LineTooLong(pos) => write!(f, "line too long at byte {}", pos),
+            ParseError::TooManyParams(pos) => write!(f, "too many parameters at byte {}", pos),
+            ParseError::NulByte(pos) => write!(f, "NUL byte at byte {}", pos),
+            ParseError::BareLineBreak(pos) => write!(f, "line break at byte {}", pos),
+            ParseError::InvalidPrefix(pos) => write!(f, "invalid prefix at byte {}", pos),
+            ParseError::InvalidCommand(pos) => write!(f, "invalid command at byte {}", pos),
+            ParseError::MissingCommand(pos) => write!(f, "missing command at byte {}", pos),
+        }
+    }
+
 }
 
+impl error::Error for ParseError {}
+
+// Maximum length of a line, without the tag section, including the "\r\n".
+pub(crate) const MAX_LINE_LENGTH: usize = 512;
+// Maximum length of the tag section, including the '@' and the space.
+pub(crate) const MAX_TAGS_LENGTH: usize = 8191;
+// Maximum length of the tag section sent by a client, 4094 bytes of tags plus the '@' and the space.
+pub(crate) const MAX_CLIENT_TAGS_LENGTH: usize = 4094 + 2;
+// Maximum amount of parameters.
+pub(crate) const MAX_PARAMS: usize = 15;
+
 /// Represents a message received from the server.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Message {
+    /// IRCv3 message tags, in the order they were received.
+    pub tags: Vec<Tag>,
     /// Prefix
     pub prefix: Option<Prefix>,
     /// Code
     pub code: Code,
     /// Arguments
     pub args: Vec<String>,
+    /// Whether the last argument was sent as a trailing parameter, with a colon.
+    ///
+    /// This is only a hint for serialization. The colon is always added when the
+    /// last argument requires it. Without arguments there is no trailing parameter,
+    /// so it is ignored, and parsing always sets it to `false`.
+    pub trailing: bool,
 }
 
 impl Message {
@@ -27,128 +102,621 @@ impl Message {
     /// Parse the given string into a `Message` struct.
     ///
     /// An error is returned if the message is not valid.
+    /// See `MessageRef::parse` for a version which does not allocate.
     pub fn parse(line: &str) -> Result<Message, ParseError> {
-        if line.len() == 0 || line.trim().len() == 0 {
+        MessageRef::parse(line).map(|msg| msg.to_owned())
+    }
+
+    /// Parse the given string into a `Message` struct, following the RFC strictly.
+    ///
+    /// See `MessageRef::parse_strict` for the extra rules.
+    pub fn parse_strict(line: &str) -> Result<Message, ParseError> {
+        MessageRef::parse_strict(line).map(|msg| msg.to_owned())
+    }
+
+    /// Serialize the message into a line ready to be sent, including the `\r\n`.
+    ///
+    /// The `Display` implementation produces the same line, without the line ending.
+    pub fn to_line(&self) -> String {
+        format!("{}\r\n", self)
+    }
+
+    /// Get the tag with the given key.
+    ///
+    /// If the key appears more than once, the last occurrence is returned.
+    pub fn tag(&self, key: &str) -> Option<&Tag> {
+        self.tags.iter().rev().find(|tag| tag.key == key)
+    }
+}
+
+fn validate_tags(line: &str, text: &str) -> Result<(), ParseError> {
+    let mut count = 0;
+    for tag in (Tags { iter: text.split(';') }) {
+        if tag.key.is_empty() {
+            return Err(ParseError::EmptyTagKey(offset(line, tag.key)));
+        }
+        if !is_valid_tag_key(tag.key) {
+            return Err(ParseError::InvalidTagKey(offset(line, tag.key)));
+        }
+        count += 1;
+    }
+    if count == 0 {
+        return Err(ParseError::EmptyTags(offset(line, text) - 1));
+    }
+    Ok(())
+}
+
+// Byte offset of a slice within the line it was taken from.
+fn offset(line: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - line.as_ptr() as usize
+}
+
+fn validate_prefix(prefix: &str, start: usize) -> Result<(), ParseError> {
+    if prefix.is_empty() {
+        return Err(ParseError::InvalidPrefix(start - 1));
+    }
+    let mut seen_exc = false;
+    let mut seen_at = false;
+    for (idx, b) in prefix.bytes().enumerate() {
+        let ok = match b {
+            b'!' => !seen_exc && !seen_at && idx > 0,
+            b'@' => !seen_at && idx > 0,
+            b',' | b'*' | b'?' => false,
+            _ => !b.is_ascii_control(),
+        };
+        if !ok {
+            return Err(ParseError::InvalidPrefix(start + idx));
+        }
+        seen_exc |= b == b'!';
+        seen_at |= b == b'@';
+    }
+    Ok(())
+}
+
+pub(crate) fn is_valid_tag_key(key: &str) -> bool {
+    let name = key.strip_prefix('+').unwrap_or(key);
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '/')
+}
+
+fn unescape_tag_value(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        // A lone backslash at the end of the value is dropped.
+        match chars.next() {
+            Some(':') => res.push(';'),
+            Some('s') => res.push(' '),
+            Some('\\') => res.push('\\'),
+            Some('r') => res.push('\r'),
+            Some('n') => res.push('\n'),
+            Some(other) => res.push(other),
+            None => {}
+        }
+    }
+    res
+}
+
+fn escape_tag_value(value: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            ';' => f.write_str("\\:")?,
+            ' ' => f.write_str("\\s")?,
+            '\\' => f.write_str("\\\\")?,
+            '\r' => f.write_str("\\r")?,
+            '\n' => f.write_str("\\n")?,
+            _ => write!(f, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+fn parse_prefix<'a>(prefix: &'a str, numeric: bool) -> PrefixRef<'a> {
+    let (rest, hostname) = match prefix.find('@') {
+        None => (prefix, None),
+        Some(atpos) => (&prefix[..atpos], Some(&prefix[atpos + 1..])),
+    };
+    let (nickname, username) = match rest.find('!') {
+        None => (rest, None),
+        Some(excpos) => (&rest[..excpos], Some(&rest[excpos + 1..])),
+    };
+
+    // A lone name is a server if it has a dot, nicknames cannot contain one,
+    // or if it sent a numeric, which only servers do.
+    if username.is_none() && hostname.is_none() && (numeric || nickname.contains('.')) {
+        return PrefixRef::Server(nickname);
+    }
+
+    PrefixRef::User(PrefixUserRef {
+        nickname,
+        username,
+        hostname,
+    })
+}
+
+/// IRCv3 message tag.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tag {
+    /// Key, including the client-only `+` and the vendor if present.
+    pub key: String,
+    /// Unescaped value, `None` if the tag has no value.
+    ///
+    /// An empty value is equivalent to a missing one and is serialized as such,
+    /// `Tag::new` stores it as `None`.
+    pub value: Option<String>,
+}
+
+impl Tag {
+
+    /// Create a tag, an empty value is stored as `None`.
+    pub fn new<K: Into<String>>(key: K, value: Option<String>) -> Tag {
+        Tag {
+            key: key.into(),
+            value: value.filter(|value| !value.is_empty()),
+        }
+    }
+
+}
+
+impl fmt::Display for Tag {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.key)?;
+        match self.value {
+            Some(ref value) if !value.is_empty() => {
+                f.write_str("=")?;
+                escape_tag_value(value, f)
+            }
+            _ => Ok(()),
+        }
+    }
+
+}
+
+/// Prefix of the message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Prefix {
+    /// Prefix is a user.
+    User(PrefixUser),
+    /// Prefix is a server.
+    Server(String),
+}
+
+/// User prefix representation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrefixUser {
+    /// Nickname
+    pub nickname: String,
+    /// Username, absent in the short `nick` and `nick@host` forms.
+    pub username: Option<String>,
+    /// Hostname, absent in the short `nick` and `nick!user` forms.
+    pub hostname: Option<String>,
+}
+
+impl Prefix {
+
+    /// Parse a prefix, without the leading colon.
+    ///
+    /// A name without `!` or `@` is considered a server if it contains a dot,
+    /// a nickname otherwise. When parsing a message, the prefix of a numeric
+    /// reply is always a server.
+    pub fn parse(prefix: &str) -> Prefix {
+        parse_prefix(prefix, false).to_owned()
+    }
+
+}
+
+impl fmt::Display for Prefix {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Prefix::User(ref user) => user.fmt(f),
+            Prefix::Server(ref name) => f.write_str(name),
+        }
+    }
+
+}
+
+impl PrefixUser {
+
+    /// Create a full `nick!user@host` prefix.
+    pub fn new(nick: &str, user: &str, host: &str) -> PrefixUser {
+        PrefixUser {
+            nickname: nick.into(),
+            username: Some(user.into()),
+            hostname: Some(host.into()),
+        }
+    }
+
+}
+
+impl fmt::Display for PrefixUser {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.nickname)?;
+        if let Some(ref username) = self.username {
+            write!(f, "!{}", username)?;
+        }
+        if let Some(ref hostname) = self.hostname {
+            write!(f, "@{}", hostname)?;
+        }
+        Ok(())
+    }
+
+}
+
+/// Borrowed representation of a message, pointing into the parsed line.
+///
+/// Parsing into a `MessageRef` does not allocate. Tags and arguments are
+/// split lazily by their iterators. Use `to_owned` to get a `Message`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MessageRef<'a> {
+    // Raw tag section, without the '@'.
+    tags: Option<&'a str>,
+    // Raw prefix, without the ':'.
+    raw_prefix: Option<&'a str>,
+    /// Prefix
+    pub prefix: Option<PrefixRef<'a>>,
+    /// Command or reply, as it appears in the line.
+    pub command: &'a str,
+    // Raw argument section.
+    args: &'a str,
+    /// Whether the last argument was sent as a trailing parameter, with a colon.
+    pub trailing: bool,
+}
+
+impl<'a> MessageRef<'a> {
+
+    /// Parse the given string into a `MessageRef` struct.
+    ///
+    /// An error is returned if the message is not valid.
+    pub fn parse(line: &'a str) -> Result<MessageRef<'a>, ParseError> {
+        if line.is_empty() || line.trim().is_empty() {
             return Err(ParseError::EmptyMessage);
         }
 
-        let mut state = line.trim_right_matches("\r\n");
-        let mut prefix: Option<Prefix> = None;
-        let code: Option<&str>;
-        let mut args: Vec<String> = Vec::new();
+        let mut state = line.trim_end_matches("\r\n");
+        let mut tags: Option<&str> = None;
+        let mut raw_prefix: Option<&str> = None;
+        let command: &str;
+
+        // Look for tags
+        if state.starts_with('@') {
+            match state.find(' ') {
+                None => return Err(ParseError::UnexpectedEnd),
+                Some(idx) => {
+                    let text = &state[1..idx];
+                    validate_tags(line, text)?;
+                    tags = Some(text);
+                    state = state[idx + 1..].trim_start_matches(' ');
+                }
+            }
+        }
 
         // Look for a prefix
-        if state.starts_with(":") {
-            match state.find(" ") {
+        if state.starts_with(':') {
+            match state.find(' ') {
                 None => return Err(ParseError::UnexpectedEnd),
                 Some(idx) => {
-                    prefix = parse_prefix(&state[1..idx]);
+                    raw_prefix = Some(&state[1..idx]);
                     state = &state[idx + 1..];
                 }
             }
         }
 
         // Look for the command/reply
-        match state.find(" ") {
+        match state.find(' ') {
             None => {
-                if state.len() == 0 {
+                if state.is_empty() {
                     return Err(ParseError::EmptyMessage);
-                } else {
-                    code = Some(&state[..]);
-                    state = &state[state.len()..];
                 }
+                command = state;
+                state = &state[state.len()..];
             }
             Some(idx) => {
-                code = Some(state[..idx].into());
+                command = &state[..idx];
                 state = &state[idx + 1..];
             }
         }
 
-        // Look for arguments and the suffix
-        if state.len() > 0 {
-            loop {
-                if state.starts_with(":") {
-                    args.push(state[1..].into());
-                    break;
-                } else {
-                    match state.find(" ") {
-                        None => {
-                            args.push(state[..].into());
-                            break;
-                        }
-                        Some(idx) => {
-                            args.push(state[..idx].into());
-                            state = &state[idx + 1..];
-                        }
-                    }
-                }
+        if command.is_empty() {
+            return Err(ParseError::EmptyCommand);
+        }
+
+        // Walk the arguments once to find out if there is a trailing parameter.
+        let mut args = Args::new(state);
+        while args.next().is_some() {}
+
+        let numeric = command.len() == 3 && command.bytes().all(|b| b.is_ascii_digit());
+        Ok(MessageRef {
+            tags,
+            raw_prefix,
+            prefix: raw_prefix.map(|prefix| parse_prefix(prefix, numeric)),
+            command,
+            args: state,
+            trailing: args.trailing,
+        })
+    }
+
+    /// Parse the given string into a `MessageRef` struct, following the RFC strictly.
+    ///
+    /// On top of the regular rules, this rejects lines longer than 512 bytes
+    /// (tag section excluded, which is limited to 8191 bytes), lines with more
+    /// than 15 parameters, NUL bytes, CR or LF anywhere but at the end of the line,
+    /// invalid prefixes and invalid commands. The errors carry the byte offset
+    /// of the problem in the given string.
+    pub fn parse_strict(line: &'a str) -> Result<MessageRef<'a>, ParseError> {
+        let body = line.strip_suffix('\n').unwrap_or(line);
+        let body = body.strip_suffix('\r').unwrap_or(body);
+
+        if let Some(pos) = body.bytes().position(|b| b == 0) {
+            return Err(ParseError::NulByte(pos));
+        }
+        if let Some(pos) = body.bytes().position(|b| b == b'\r' || b == b'\n') {
+            return Err(ParseError::BareLineBreak(pos));
+        }
+
+        // The tag section has its own limit.
+        let tags_len = match body.find(' ') {
+            Some(idx) if body.starts_with('@') => idx + 1,
+            _ => 0,
+        };
+        if tags_len > MAX_TAGS_LENGTH {
+            return Err(ParseError::LineTooLong(MAX_TAGS_LENGTH));
+        }
+        // Keep two bytes for the "\r\n".
+        if body.len() - tags_len > MAX_LINE_LENGTH - 2 {
+            return Err(ParseError::LineTooLong(tags_len + MAX_LINE_LENGTH - 2));
+        }
+
+        let msg = MessageRef::parse(body).map_err(|err| match err {
+            ParseError::UnexpectedEnd => ParseError::MissingCommand(body.len()),
+            err => err,
+        })?;
+
+        if let Some(raw) = msg.raw_prefix {
+            validate_prefix(raw, offset(line, raw))?;
+        }
+
+        let command_ok = msg.command.bytes().all(|b| b.is_ascii_alphabetic()) ||
+                         (msg.command.len() == 3 && msg.command.bytes().all(|b| b.is_ascii_digit()));
+        if !command_ok {
+            return Err(ParseError::InvalidCommand(offset(line, msg.command)));
+        }
+
+        if let Some(arg) = msg.args().nth(MAX_PARAMS) {
+            // Point at the colon of a trailing parameter.
+            let mut pos = offset(line, arg);
+            if msg.trailing && msg.args().count() == MAX_PARAMS + 1 {
+                pos -= 1;
             }
+            return Err(ParseError::TooManyParams(pos));
         }
 
-        let code = match code {
-            None => return Err(ParseError::EmptyCommand),
-            Some(text) => {
-                match text.parse() {
-                    Ok(code) => code,
-                    Err(_) => Code::Unknown(text.into()),
-                }
+        Ok(msg)
+    }
+
+    /// Iterate over the tags, in the order they were received.
+    pub fn tags(&self) -> Tags<'a> {
+        Tags {
+            iter: self.tags.unwrap_or("").split(';'),
+        }
+    }
+
+    /// Get the tag with the given key.
+    ///
+    /// If the key appears more than once, the last occurrence is returned.
+    pub fn tag(&self, key: &str) -> Option<TagRef<'a>> {
+        self.tags().filter(|tag| tag.key == key).last()
+    }
+
+    /// Iterate over the arguments.
+    pub fn args(&self) -> Args<'a> {
+        Args::new(self.args)
+    }
+
+    /// Get the argument at the given position.
+    pub fn arg(&self, idx: usize) -> Option<&'a str> {
+        self.args().nth(idx)
+    }
+
+    /// Get the code of the message.
+    ///
+    /// This allocates if the code is unknown.
+    pub fn code(&self) -> Code {
+        match self.command.parse() {
+            Ok(code) => code,
+            Err(_) => Code::Unknown(self.command.into()),
+        }
+    }
+
+    /// Convert into an owned `Message`.
+    pub fn to_owned(self) -> Message {
+        Message {
+            tags: self.tags().map(|tag| tag.to_owned()).collect(),
+            prefix: self.prefix.map(|prefix| prefix.to_owned()),
+            code: self.code(),
+            args: self.args().map(|arg| arg.into()).collect(),
+            trailing: self.trailing,
+        }
+    }
+
+}
+
+/// Iterator over the tags of a `MessageRef`.
+#[derive(Clone, Debug)]
+pub struct Tags<'a> {
+    iter: str::Split<'a, char>,
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = TagRef<'a>;
+
+    fn next(&mut self) -> Option<TagRef<'a>> {
+        loop {
+            let raw = self.iter.next()?;
+            // A trailing semicolon is tolerated.
+            if raw.is_empty() {
+                continue;
             }
-        };
+            return Some(match raw.find('=') {
+                None => TagRef { key: raw, value: None },
+                // An empty value is equivalent to a missing value.
+                Some(idx) if idx + 1 == raw.len() => TagRef { key: &raw[..idx], value: None },
+                Some(idx) => TagRef { key: &raw[..idx], value: Some(&raw[idx + 1..]) },
+            });
+        }
+    }
+}
 
-        Ok(Message {
-            prefix: prefix,
-            code: code,
-            args: args,
-        })
+/// Iterator over the arguments of a `MessageRef`.
+#[derive(Clone, Debug)]
+pub struct Args<'a> {
+    // None once every argument was returned.
+    state: Option<&'a str>,
+    trailing: bool,
+}
+
+impl<'a> Args<'a> {
+
+    fn new(state: &'a str) -> Args<'a> {
+        Args {
+            state: if state.is_empty() { None } else { Some(state) },
+            trailing: false,
+        }
     }
+
 }
 
-fn parse_prefix(prefix: &str) -> Option<Prefix> {
-    match prefix.find("!") {
-        None => Some(Prefix::Server(prefix.to_string())),
-        Some(excpos) => {
-            let nick = &prefix[..excpos];
-            let rest = &prefix[excpos + 1..];
-            match rest.find("@") {
-                None => return None,
-                Some(atpos) => {
-                    let user = &rest[..atpos];
-                    let host = &rest[atpos + 1..];
-                    return Some(Prefix::User(PrefixUser::new(nick, user, host)));
-                }
+impl<'a> Iterator for Args<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let state = self.state?;
+        if let Some(rest) = state.strip_prefix(':') {
+            self.state = None;
+            self.trailing = true;
+            return Some(rest);
+        }
+        match state.find(' ') {
+            None => {
+                self.state = None;
+                Some(state)
+            }
+            Some(idx) => {
+                self.state = Some(&state[idx + 1..]);
+                Some(&state[..idx])
             }
         }
     }
 }
 
-/// Prefix of the message.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Prefix {
+/// Borrowed IRCv3 message tag.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TagRef<'a> {
+    /// Key, including the client-only `+` and the vendor if present.
+    pub key: &'a str,
+    /// Value as it appears in the line, still escaped.
+    pub value: Option<&'a str>,
+}
+
+impl<'a> TagRef<'a> {
+
+    /// Convert into an owned `Tag`, unescaping the value.
+    pub fn to_owned(self) -> Tag {
+        // A value made of a lone backslash unescapes to nothing.
+        Tag::new(self.key, self.value.map(unescape_tag_value))
+    }
+
+}
+
+/// Borrowed prefix of the message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PrefixRef<'a> {
     /// Prefix is a user.
-    User(PrefixUser),
+    User(PrefixUserRef<'a>),
     /// Prefix is a server.
-    Server(String),
+    Server(&'a str),
 }
 
-/// User prefix representation.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PrefixUser {
+impl<'a> PrefixRef<'a> {
+
+    /// Convert into an owned `Prefix`.
+    pub fn to_owned(self) -> Prefix {
+        match self {
+            PrefixRef::User(user) => Prefix::User(user.to_owned()),
+            PrefixRef::Server(name) => Prefix::Server(name.into()),
+        }
+    }
+
+}
+
+/// Borrowed user prefix representation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PrefixUserRef<'a> {
     /// Nickname
-    pub nickname: String,
-    /// Username
-    pub username: String,
-    /// Hostname
-    pub hostname: String,
+    pub nickname: &'a str,
+    /// Username, absent in the short `nick` and `nick@host` forms.
+    pub username: Option<&'a str>,
+    /// Hostname, absent in the short `nick` and `nick!user` forms.
+    pub hostname: Option<&'a str>,
 }
 
-impl PrefixUser {
+impl<'a> PrefixUserRef<'a> {
 
-    fn new(nick: &str, user: &str, host: &str) -> PrefixUser {
+    /// Convert into an owned `PrefixUser`.
+    pub fn to_owned(self) -> PrefixUser {
         PrefixUser {
-            nickname: nick.into(),
-            username: user.into(),
-            hostname: host.into(),
+            nickname: self.nickname.into(),
+            username: self.username.map(|username| username.into()),
+            hostname: self.hostname.map(|hostname| hostname.into()),
+        }
+    }
+
+}
+
+/// Serialize the message without the line ending.
+///
+/// A colon is added before the last argument when it is needed, or when
+/// the `trailing` flag is set.
+impl fmt::Display for Message {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.tags.is_empty() {
+            f.write_str("@")?;
+            for (idx, tag) in self.tags.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(";")?;
+                }
+                tag.fmt(f)?;
+            }
+            f.write_str(" ")?;
         }
+
+        if let Some(ref prefix) = self.prefix {
+            write!(f, ":{} ", prefix)?;
+        }
+
+        self.code.fmt(f)?;
+
+        if let Some((last, middle)) = self.args.split_last() {
+            for arg in middle {
+                write!(f, " {}", arg)?;
+            }
+            if self.trailing || last.is_empty() || last.starts_with(':') || last.contains(' ') {
+                write!(f, " :{}", last)?;
+            } else {
+                write!(f, " {}", last)?;
+            }
+        }
+
+        Ok(())
     }
 
 }
@@ -162,6 +730,44 @@ fn test_full() {
     assert_eq!(msg.args, vec!["arg1", "arg2", "arg3", "suffix is pretty cool yo"]);
 }
 
+#[test]
+fn test_modern_codes() {
+    assert_eq!(Message::parse("CAP * LS :sasl").unwrap().code, Code::Cap);
+    assert_eq!(Message::parse(":irc.example.com 005 bob NICKLEN=30 :are supported").unwrap().code, Code::RplIsupport);
+    let msg = Message::parse(":irc.example.com 904 bob :SASL authentication failed").unwrap();
+    assert_eq!(msg.code, Code::ErrSaslfail);
+    assert!(msg.code.is_error());
+    assert!(Code::RplTopicwhotime.is_reply());
+    assert_eq!(Code::RplMononline.to_string(), "730");
+}
+
+#[test]
+fn test_numeric_codes() {
+    use code::CodeKind;
+
+    assert_eq!("privmsg".parse(), Ok(Code::Privmsg));
+    assert_eq!("Foo".parse(), Ok(Code::Unknown("Foo".into())));
+    assert_eq!("AuthEnticate".parse(), Ok(Code::Authenticate));
+    assert_eq!("privmsgprivmsg".parse(), Ok(Code::Unknown("privmsgprivmsg".into())));
+    assert_eq!("433".parse(), Ok(Code::ErrNicknameinuse));
+    assert_eq!("999".parse(), Ok(Code::Numeric(999)));
+    assert_eq!(Code::from_numeric(1), Code::RplWelcome);
+    assert_eq!(Code::from_numeric(42), Code::Numeric(42));
+    assert_eq!(Code::Numeric(42).to_string(), "042");
+    assert_eq!(Code::ErrNicknameinuse.numeric(), Some(433));
+    assert_eq!(Code::Privmsg.numeric(), None);
+    assert_eq!(Code::Numeric(499).kind(), CodeKind::Error);
+    assert_eq!(Code::Numeric(999).kind(), CodeKind::Reply);
+    assert_eq!(Code::Unknown("FOO".into()).kind(), CodeKind::Command);
+    assert_eq!(Code::RplWelcome.kind(), CodeKind::Reply);
+
+    let code = Code::ErrNicknameinuse;
+    let line = format!("{} {}: {}", code, code.name().unwrap(), code.description().unwrap());
+    assert_eq!(line, "433 ERR_NICKNAMEINUSE: Nickname is already in use");
+    assert_eq!(code.min_params(), Some(3));
+    assert_eq!(Code::Numeric(999).name(), None);
+}
+
 #[test]
 fn test_no_prefix() {
     let res = Message::parse("NICK arg1 arg2 arg3 :suffix is pretty cool yo");
@@ -247,3 +853,251 @@ fn test_prefix_user() {
     let msg = res.ok().unwrap();
     assert_eq!(msg.prefix, Some(Prefix::User(PrefixUser::new("bob", "bob", "bob.com"))));
 }
+
+#[test]
+fn test_tags() {
+    let res = Message::parse("@time=2016-02-02T12:00:00.000Z;msgid=abc;+example.com/flag :bob!bob@bob.com PRIVMSG #chan :hi");
+    assert!(res.is_ok());
+    let msg = res.ok().unwrap();
+    assert_eq!(msg.tags, vec![
+        Tag { key: "time".into(), value: Some("2016-02-02T12:00:00.000Z".into()) },
+        Tag { key: "msgid".into(), value: Some("abc".into()) },
+        Tag { key: "+example.com/flag".into(), value: None },
+    ]);
+    assert_eq!(msg.prefix, Some(Prefix::User(PrefixUser::new("bob", "bob", "bob.com"))));
+    assert_eq!(msg.code, Code::Privmsg);
+    assert_eq!(msg.args, vec!["#chan", "hi"]);
+}
+
+#[test]
+fn test_tags_no_prefix() {
+    let res = Message::parse("@a=b PING :server");
+    assert!(res.is_ok());
+    let msg = res.ok().unwrap();
+    assert_eq!(msg.prefix, None);
+    assert_eq!(msg.code, Code::Ping);
+    assert_eq!(msg.tag("a").unwrap().value, Some("b".into()));
+}
+
+#[test]
+fn test_tags_escapes() {
+    let res = Message::parse("@a=x\\:y\\sz\\\\w\\r\\n\\q;b=;c=end\\ PING");
+    assert!(res.is_ok());
+    let msg = res.ok().unwrap();
+    assert_eq!(msg.tag("a").unwrap().value, Some("x;y z\\w\r\nq".into()));
+    assert_eq!(msg.tag("b").unwrap().value, None);
+    assert_eq!(msg.tag("c").unwrap().value, Some("end".into()));
+}
+
+#[test]
+fn test_tags_last_wins() {
+    let msg = Message::parse("@a=1;a=2 PING").unwrap();
+    assert_eq!(msg.tags.len(), 2);
+    assert_eq!(msg.tag("a").unwrap().value, Some("2".into()));
+}
+
+#[test]
+fn test_tags_errors() {
+    assert_eq!(Message::parse("@ PING"), Err(ParseError::EmptyTags(0)));
+    assert_eq!(Message::parse("@=b PING"), Err(ParseError::EmptyTagKey(1)));
+    assert_eq!(Message::parse("@a!b=c PING"), Err(ParseError::InvalidTagKey(1)));
+    assert_eq!(Message::parse("@a=b"), Err(ParseError::UnexpectedEnd));
+    assert_eq!(Message::parse("@a=b "), Err(ParseError::EmptyMessage));
+}
+
+#[test]
+fn test_trailing() {
+    assert!(Message::parse("PRIVMSG #chan :hi").unwrap().trailing);
+    assert!(!Message::parse("PRIVMSG #chan hi").unwrap().trailing);
+    assert!(!Message::parse("NICK").unwrap().trailing);
+}
+
+#[test]
+fn test_to_line() {
+    let msg = Message {
+        tags: vec![Tag { key: "a".into(), value: Some("x y;z".into()) }],
+        prefix: Some(Prefix::User(PrefixUser::new("bob", "bob", "bob.com"))),
+        code: Code::Privmsg,
+        args: vec!["#chan".into(), "hello world".into()],
+        trailing: false,
+    };
+    assert_eq!(msg.to_line(), "@a=x\\sy\\:z :bob!bob@bob.com PRIVMSG #chan :hello world\r\n");
+    assert_eq!(Message::parse(&msg.to_line()).unwrap().args, msg.args);
+}
+
+#[test]
+fn test_round_trip_edge_cases() {
+    // Empty tag values are missing values.
+    let msg = Message::builder(Code::Tagmsg).tag("a", Some("")).arg("#chan").build().unwrap();
+    assert_eq!(msg.tags[0].value, None);
+    assert_eq!(Message::parse(&msg.to_line()), Ok(msg));
+    let msg = Message::parse("@a=\\ TAGMSG #chan").unwrap();
+    assert_eq!(msg.tags[0], Tag::new("a", None));
+    assert_eq!(Message::parse(&msg.to_line()), Ok(msg));
+    assert_eq!(Tag::new("a", Some(String::new())), Tag::new("a", None));
+
+    // Without arguments there is no trailing parameter.
+    let msg = Message::builder(Code::Ping).build().unwrap();
+    assert!(!msg.trailing);
+    assert_eq!(Message::parse(&msg.to_line()), Ok(msg));
+    let msg = Message::parse("PING :").unwrap();
+    assert_eq!(msg.args, vec![""]);
+    assert_eq!(Message::parse(&msg.to_line()), Ok(msg));
+}
+
+#[test]
+fn test_to_line_trailing_needed() {
+    let mut msg = Message::parse("TOPIC #chan x").unwrap();
+    msg.args[1] = "".into();
+    assert_eq!(msg.to_string(), "TOPIC #chan :");
+    msg.args[1] = ":)".into();
+    assert_eq!(msg.to_string(), "TOPIC #chan ::)");
+}
+
+#[test]
+fn test_round_trip() {
+    let lines = [
+        "NICK",
+        "PING irc.freenode.net",
+        "PRIVMSG #chan :hi",
+        ":irc.freenode.net 001 bob :Welcome to freenode",
+        ":bob!bob@bob.com PRIVMSG #chan :suffix is pretty cool yo",
+        "@time=2016-02-02T12:00:00.000Z;+flag;msg=a\\sb\\\\c :bob!bob@bob.com COMMAND arg1 arg2 :",
+    ];
+    for line in lines.iter() {
+        let msg = Message::parse(line).unwrap();
+        assert_eq!(&msg.to_string(), line);
+        assert_eq!(Message::parse(&msg.to_line()).unwrap(), msg);
+    }
+}
+
+#[test]
+fn test_message_ref() {
+    let line = "@a=x\\sy;b :bob!bob@bob.com PRIVMSG #chan :hello world";
+    let msg = MessageRef::parse(line).unwrap();
+    assert_eq!(msg.command, "PRIVMSG");
+    assert_eq!(msg.prefix, Some(PrefixRef::User(PrefixUserRef { nickname: "bob", username: Some("bob"), hostname: Some("bob.com") })));
+    assert_eq!(msg.args().collect::<Vec<_>>(), vec!["#chan", "hello world"]);
+    assert_eq!(msg.arg(1), Some("hello world"));
+    assert_eq!(msg.arg(2), None);
+    assert_eq!(msg.tag("a"), Some(TagRef { key: "a", value: Some("x\\sy") }));
+    assert_eq!(msg.tag("b"), Some(TagRef { key: "b", value: None }));
+    assert!(msg.trailing);
+    assert_eq!(msg.to_owned(), Message::parse(line).unwrap());
+}
+
+#[test]
+fn test_message_ref_empty_args() {
+    let msg = MessageRef::parse("NICK").unwrap();
+    assert_eq!(msg.args().count(), 0);
+    assert_eq!(msg.tags().count(), 0);
+    assert_eq!(msg.code(), Code::Nick);
+}
+
+#[test]
+fn test_strict_ok() {
+    let line = "@time=2016-02-02T12:00:00.000Z :bob!~bob@bob.com PRIVMSG #chan :hello world\r\n";
+    assert_eq!(Message::parse_strict(line), Message::parse(line));
+    assert!(Message::parse_strict(":irc.example.com 001 bob :Welcome").is_ok());
+}
+
+#[test]
+fn test_strict_too_long() {
+    let line = format!("PRIVMSG #chan :{}", "a".repeat(500));
+    assert_eq!(Message::parse_strict(&line), Err(ParseError::LineTooLong(510)));
+    let line = format!("@a={} PRIVMSG #chan :{}", "b".repeat(1000), "a".repeat(490));
+    assert!(Message::parse_strict(&line).is_ok());
+}
+
+#[test]
+fn test_strict_too_many_params() {
+    let line = "CMD 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 :16 17";
+    assert_eq!(Message::parse_strict(line), Err(ParseError::TooManyParams(40)));
+    assert!(Message::parse_strict("CMD 1 2 3 4 5 6 7 8 9 10 11 12 13 14 :15").is_ok());
+}
+
+#[test]
+fn test_strict_bad_bytes() {
+    assert_eq!(Message::parse_strict("PRIVMSG #chan :a\0b"), Err(ParseError::NulByte(16)));
+    assert_eq!(Message::parse_strict("PRIVMSG #chan :a\rb\r\n"), Err(ParseError::BareLineBreak(16)));
+    assert_eq!(Message::parse_strict("PRIVMSG #chan :a\nb"), Err(ParseError::BareLineBreak(16)));
+}
+
+#[test]
+fn test_strict_prefix_and_command() {
+    assert_eq!(Message::parse_strict(":bob!b!ob@bob.com PING x"), Err(ParseError::InvalidPrefix(6)));
+    assert_eq!(Message::parse_strict("@a=b :bo*b PING x"), Err(ParseError::InvalidPrefix(8)));
+    assert_eq!(Message::parse_strict(": PING x"), Err(ParseError::InvalidPrefix(0)));
+    assert_eq!(Message::parse_strict(":bob PR1V x"), Err(ParseError::InvalidCommand(5)));
+    assert_eq!(Message::parse_strict("0001 x"), Err(ParseError::InvalidCommand(0)));
+}
+
+#[test]
+fn test_strict_tags_and_end() {
+    assert_eq!(Message::parse_strict("@; PING"), Err(ParseError::EmptyTags(0)));
+    assert_eq!(Message::parse_strict("@a=b;=c PING"), Err(ParseError::EmptyTagKey(5)));
+    assert_eq!(Message::parse_strict("@a=b;c_d PING"), Err(ParseError::InvalidTagKey(5)));
+    assert_eq!(Message::parse_strict("@a=b :bob\r\n"), Err(ParseError::MissingCommand(9)));
+    assert_eq!(Message::parse_strict("@a=b"), Err(ParseError::MissingCommand(4)));
+}
+
+#[test]
+fn test_prefix_nick_only() {
+    let msg = Message::parse(":bob MODE bob :+i").unwrap();
+    assert_eq!(msg.prefix, Some(Prefix::User(PrefixUser {
+        nickname: "bob".into(),
+        username: None,
+        hostname: None,
+    })));
+}
+
+#[test]
+fn test_prefix_server_without_dot() {
+    let msg = Message::parse(":localhost 001 bob :Welcome").unwrap();
+    assert_eq!(msg.prefix, Some(Prefix::Server("localhost".into())));
+    let msg = Message::parse(":localhost NOTICE bob :hi").unwrap();
+    assert_eq!(msg.prefix, Some(Prefix::User(PrefixUser {
+        nickname: "localhost".into(),
+        username: None,
+        hostname: None,
+    })));
+}
+
+#[test]
+fn test_prefix_nick_user() {
+    let msg = Message::parse(":bob!bob PRIVMSG #chan :hi").unwrap();
+    assert_eq!(msg.prefix, Some(Prefix::User(PrefixUser {
+        nickname: "bob".into(),
+        username: Some("bob".into()),
+        hostname: None,
+    })));
+}
+
+#[test]
+fn test_prefix_nick_host() {
+    let msg = Message::parse(":bob@bob.com PRIVMSG #chan :hi").unwrap();
+    assert_eq!(msg.prefix, Some(Prefix::User(PrefixUser {
+        nickname: "bob".into(),
+        username: None,
+        hostname: Some("bob.com".into()),
+    })));
+}
+
+#[test]
+fn test_prefix_round_trip() {
+    for prefix in ["bob", "bob!bob", "bob@bob.com", "bob!bob@bob.com", "irc.freenode.net"].iter() {
+        assert_eq!(&Prefix::parse(prefix).to_string(), prefix);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let msg = Message::parse("@id=1 :bob!bob@bob.com PRIVMSG #chan :hello world").unwrap();
+    let json = ::serde_json::to_value(&msg).unwrap();
+    assert_eq!(json["code"], "PRIVMSG");
+    assert_eq!(json["prefix"]["User"]["nickname"], "bob");
+    assert_eq!(::serde_json::from_value::<Message>(json).unwrap(), msg);
+    let json = ::serde_json::to_string(&Message::parse("999 bob").unwrap()).unwrap();
+    assert_eq!(::serde_json::from_str::<Message>(&json).unwrap().code, Code::Numeric(999));
+}
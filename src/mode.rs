@@ -0,0 +1,280 @@
+use code::Code;
+use message::Message;
+
+/// How a channel mode takes its parameter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModeClass {
+    /// Type A, list modes such as bans. Always take a parameter, except when listing.
+    List,
+    /// Type B, always take a parameter, such as the key.
+    Always,
+    /// Type C, take a parameter only when set, such as the limit.
+    WhenSet,
+    /// Type D, never take a parameter.
+    Never,
+    /// Membership modes from the `PREFIX` token, such as op. Always take a nickname.
+    Prefix,
+}
+
+/// Classes of the channel modes, given by the `CHANMODES` and `PREFIX` ISUPPORT tokens.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelModes {
+    /// Type A modes.
+    pub list: String,
+    /// Type B modes.
+    pub always: String,
+    /// Type C modes.
+    pub when_set: String,
+    /// Type D modes.
+    pub never: String,
+    /// Membership modes, in order of rank.
+    pub prefix: String,
+}
+
+/// Defaults from RFC 2811, `CHANMODES=beI,k,l,imnpst` and `PREFIX=(ov)@+`.
+impl Default for ChannelModes {
+
+    fn default() -> ChannelModes {
+        ChannelModes::from_tokens("beI,k,l,imnpst", "(ov)@+")
+    }
+
+}
+
+impl ChannelModes {
+
+    /// Create the classes from the values of the `CHANMODES` and `PREFIX` tokens.
+    ///
+    /// Missing classes are empty. Extra classes are ignored, as the specification says.
+    pub fn from_tokens(chanmodes: &str, prefix: &str) -> ChannelModes {
+        let mut classes = chanmodes.split(',');
+        let mut next = || classes.next().unwrap_or("").to_string();
+        ChannelModes {
+            list: next(),
+            always: next(),
+            when_set: next(),
+            never: next(),
+            prefix: prefix.strip_prefix('(')
+                          .and_then(|rest| rest.find(')').map(|idx| &rest[..idx]))
+                          .unwrap_or("")
+                          .into(),
+        }
+    }
+
+    /// Get the class of a mode.
+    ///
+    /// Unknown modes are assumed to never take a parameter.
+    pub fn class(&self, mode: char) -> ModeClass {
+        if self.prefix.contains(mode) {
+            ModeClass::Prefix
+        } else if self.list.contains(mode) {
+            ModeClass::List
+        } else if self.always.contains(mode) {
+            ModeClass::Always
+        } else if self.when_set.contains(mode) {
+            ModeClass::WhenSet
+        } else {
+            ModeClass::Never
+        }
+    }
+
+    /// Check if the mode takes a parameter when added or removed.
+    pub fn takes_arg(&self, mode: char, add: bool) -> bool {
+        match self.class(mode) {
+            ModeClass::List | ModeClass::Always | ModeClass::Prefix => true,
+            ModeClass::WhenSet => add,
+            ModeClass::Never => false,
+        }
+    }
+
+}
+
+/// A single mode being added or removed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModeChange {
+    /// `true` for `+`, `false` for `-`.
+    pub add: bool,
+    /// Mode character.
+    pub mode: char,
+    /// Parameter, if the mode takes one.
+    ///
+    /// List modes without a parameter are list queries, as in `MODE #chan +b`.
+    pub arg: Option<String>,
+}
+
+impl ModeChange {
+
+    /// Create a new mode change.
+    pub fn new(add: bool, mode: char, arg: Option<String>) -> ModeChange {
+        ModeChange {
+            add,
+            mode,
+            arg,
+        }
+    }
+
+    /// Parse the channel mode changes, from the arguments following the channel.
+    ///
+    /// The arguments can contain more than one mode string, each followed by its
+    /// parameters, as in `+ov-b nick1 nick2 *!*@x +l 10`. Missing parameters are `None`.
+    pub fn parse_channel<S: AsRef<str>>(args: &[S], modes: &ChannelModes) -> Vec<ModeChange> {
+        parse(args, |mode, add| modes.takes_arg(mode, add))
+    }
+
+    /// Parse the user mode changes, from the arguments following the nickname.
+    pub fn parse_user<S: AsRef<str>>(args: &[S]) -> Vec<ModeChange> {
+        parse(args, |_, _| false)
+    }
+
+    /// Build the MODE messages applying the changes.
+    ///
+    /// Consecutive changes with the same sign share it, and each message carries at
+    /// most `max_modes` changes with a parameter, the `MODES` ISUPPORT token.
+    /// `None` means no limit.
+    pub fn encode(target: &str, changes: &[ModeChange], max_modes: Option<usize>) -> Vec<Message> {
+        let mut messages = Vec::new();
+        let mut modes = String::new();
+        let mut args: Vec<String> = Vec::new();
+        let mut sign: Option<bool> = None;
+
+        for change in changes {
+            let full = match max_modes {
+                Some(max) => max > 0 && args.len() >= max,
+                None => false,
+            };
+            if change.arg.is_some() && full {
+                messages.push(mode_message(target, &mut modes, &mut args));
+                sign = None;
+            }
+            if sign != Some(change.add) {
+                modes.push(if change.add { '+' } else { '-' });
+                sign = Some(change.add);
+            }
+            modes.push(change.mode);
+            args.extend(change.arg.clone());
+        }
+
+        if !modes.is_empty() {
+            messages.push(mode_message(target, &mut modes, &mut args));
+        }
+        messages
+    }
+
+}
+
+fn mode_message(target: &str, modes: &mut String, args: &mut Vec<String>) -> Message {
+    let mut all = vec![target.to_string(), ::std::mem::take(modes)];
+    all.append(args);
+    Message {
+        tags: Vec::new(),
+        prefix: None,
+        code: Code::Mode,
+        args: all,
+        trailing: false,
+    }
+}
+
+fn parse<S, F>(args: &[S], takes_arg: F) -> Vec<ModeChange>
+    where S: AsRef<str>, F: Fn(char, bool) -> bool
+{
+    let mut changes = Vec::new();
+    let mut idx = 0;
+
+    while idx < args.len() {
+        let modestring = args[idx].as_ref();
+        idx += 1;
+        // Only the first argument may omit the sign. Other leftover arguments are
+        // parameters of modes this side does not know, such as `+f 5:10`.
+        if idx > 1 && !modestring.starts_with('+') && !modestring.starts_with('-') {
+            continue;
+        }
+        let mut add = true;
+        for mode in modestring.chars() {
+            match mode {
+                '+' => add = true,
+                '-' => add = false,
+                _ => {
+                    let arg = if takes_arg(mode, add) && idx < args.len() {
+                        idx += 1;
+                        Some(args[idx - 1].as_ref().to_string())
+                    } else {
+                        None
+                    };
+                    changes.push(ModeChange::new(add, mode, arg));
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+#[test]
+fn test_parse_channel() {
+    let args = ["+ov-b", "nick1", "nick2", "*!*@x", "+l", "10"];
+    assert_eq!(ModeChange::parse_channel(&args, &ChannelModes::default()), vec![
+        ModeChange::new(true, 'o', Some("nick1".into())),
+        ModeChange::new(true, 'v', Some("nick2".into())),
+        ModeChange::new(false, 'b', Some("*!*@x".into())),
+        ModeChange::new(true, 'l', Some("10".into())),
+    ]);
+}
+
+#[test]
+fn test_parse_classes() {
+    let modes = ChannelModes::from_tokens("beI,k,fl,imnpst", "(qaohv)~&@%+");
+    let args = ["+ntk-l+q-fb", "key", "owner"];
+    assert_eq!(ModeChange::parse_channel(&args, &modes), vec![
+        ModeChange::new(true, 'n', None),
+        ModeChange::new(true, 't', None),
+        ModeChange::new(true, 'k', Some("key".into())),
+        ModeChange::new(false, 'l', None),
+        ModeChange::new(true, 'q', Some("owner".into())),
+        ModeChange::new(false, 'f', None),
+        ModeChange::new(false, 'b', None),
+    ]);
+}
+
+#[test]
+fn test_parse_leftover_args() {
+    // 'f' takes a parameter on InspIRCd, but is unknown here.
+    let modes = ChannelModes::from_tokens("b,k,l,imnst", "(ov)@+");
+    let args = ["+f", "5:10", "+n", "-t"];
+    assert_eq!(ModeChange::parse_channel(&args, &modes), vec![
+        ModeChange::new(true, 'f', None),
+        ModeChange::new(true, 'n', None),
+        ModeChange::new(false, 't', None),
+    ]);
+    assert_eq!(ModeChange::parse_user(&["i"]), vec![ModeChange::new(true, 'i', None)]);
+}
+
+#[test]
+fn test_parse_user() {
+    assert_eq!(ModeChange::parse_user(&["+iw-x"]), vec![
+        ModeChange::new(true, 'i', None),
+        ModeChange::new(true, 'w', None),
+        ModeChange::new(false, 'x', None),
+    ]);
+}
+
+#[test]
+fn test_encode() {
+    let changes = [
+        ModeChange::new(true, 'o', Some("a".into())),
+        ModeChange::new(true, 'o', Some("b".into())),
+        ModeChange::new(true, 'n', None),
+        ModeChange::new(false, 'v', Some("c".into())),
+        ModeChange::new(false, 'b', Some("*!*@x".into())),
+    ];
+    let lines: Vec<String> = ModeChange::encode("#chan", &changes, Some(3)).iter().map(|m| m.to_string()).collect();
+    assert_eq!(lines, vec!["MODE #chan +oon-v a b c", "MODE #chan -b *!*@x"]);
+    let lines: Vec<String> = ModeChange::encode("#chan", &changes, None).iter().map(|m| m.to_string()).collect();
+    assert_eq!(lines, vec!["MODE #chan +oon-vb a b c *!*@x"]);
+}
+
+#[test]
+fn test_round_trip() {
+    let modes = ChannelModes::default();
+    let msg = Message::parse("MODE #chan +o-v+l a b 10").unwrap();
+    let changes = ModeChange::parse_channel(&msg.args[1..], &modes);
+    assert_eq!(ModeChange::encode("#chan", &changes, None), vec![msg]);
+}
@@ -0,0 +1,370 @@
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::time::Duration;
+
+use transport::{self, DEFAULT_TIMEOUT};
+
+/// Proxy through which the connection to the server is made.
+///
+/// Use it with `TcpTransport::proxy`, every reconnection goes through the proxy as well.
+#[derive(Clone, Eq, PartialEq)]
+pub enum Proxy {
+    /// SOCKS5 proxy.
+    ///
+    /// Hostnames are resolved by the proxy, so names only it can resolve,
+    /// such as `.onion` ones, can be used.
+    Socks5 {
+        /// Address of the proxy, such as `127.0.0.1:9050`.
+        address: String,
+        /// Username and password, if the proxy requires them.
+        credentials: Option<(String, String)>,
+    },
+    /// HTTP proxy, with the `CONNECT` method.
+    HttpConnect {
+        /// Address of the proxy, such as `proxy.example.com:3128`.
+        address: String,
+        /// Username and password for basic authentication, if the proxy requires them.
+        credentials: Option<(String, String)>,
+    },
+}
+
+impl Proxy {
+
+    /// Create a SOCKS5 proxy without authentication.
+    pub fn socks5<A: Into<String>>(address: A) -> Proxy {
+        Proxy::Socks5 {
+            address: address.into(),
+            credentials: None,
+        }
+    }
+
+    /// Create an HTTP `CONNECT` proxy without authentication.
+    pub fn http_connect<A: Into<String>>(address: A) -> Proxy {
+        Proxy::HttpConnect {
+            address: address.into(),
+            credentials: None,
+        }
+    }
+
+    /// Authenticate with a username and a password.
+    pub fn credentials<U: Into<String>, P: Into<String>>(mut self, username: U, password: P) -> Proxy {
+        match self {
+            Proxy::Socks5 { ref mut credentials, .. } | Proxy::HttpConnect { ref mut credentials, .. } => {
+                *credentials = Some((username.into(), password.into()));
+            }
+        }
+        self
+    }
+
+    /// Connect to the target, such as `irc.example.com:6667`, through the proxy.
+    ///
+    /// The connection and each read and write of the handshake give up after 30 seconds.
+    pub fn connect(&self, target: &str) -> io::Result<TcpStream> {
+        self.connect_timeout(target, DEFAULT_TIMEOUT)
+    }
+
+    pub(crate) fn connect_timeout(&self, target: &str, timeout: Duration) -> io::Result<TcpStream> {
+        let (host, port) = split_address(target)?;
+        match *self {
+            Proxy::Socks5 { ref address, ref credentials } => {
+                let mut stream = transport::connect_timeout(&address[..], timeout)?;
+                transport::set_timeouts(&stream, Some(timeout))?;
+                socks5_handshake(&mut stream, host, port, credentials.as_ref())?;
+                transport::set_timeouts(&stream, None)?;
+                Ok(stream)
+            }
+            Proxy::HttpConnect { ref address, ref credentials } => {
+                let mut stream = transport::connect_timeout(&address[..], timeout)?;
+                transport::set_timeouts(&stream, Some(timeout))?;
+                http_connect(&mut stream, target, credentials.as_ref())?;
+                transport::set_timeouts(&stream, None)?;
+                Ok(stream)
+            }
+        }
+    }
+
+}
+
+fn proxy_error<S: Into<String>>(message: S) -> io::Error {
+    io::Error::other(message.into())
+}
+
+// Split "host:port" and "[ipv6]:port" addresses.
+fn split_address(address: &str) -> io::Result<(&str, u16)> {
+    let idx = address.rfind(':').ok_or_else(|| proxy_error(format!("missing port in {:?}", address)))?;
+    let port = address[idx + 1..].parse().map_err(|_| proxy_error(format!("invalid port in {:?}", address)))?;
+    let host = &address[..idx];
+    Ok((host.trim_start_matches('[').trim_end_matches(']'), port))
+}
+
+fn socks5_handshake(stream: &mut TcpStream, host: &str, port: u16,
+                    credentials: Option<&(String, String)>) -> io::Result<()> {
+    // Offer username/password authentication only when we have credentials.
+    match credentials {
+        Some(_) => stream.write_all(&[5, 2, 0, 2])?,
+        None => stream.write_all(&[5, 1, 0])?,
+    }
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply)?;
+    if reply[0] != 5 {
+        return Err(proxy_error("not a SOCKS5 proxy"));
+    }
+    match (reply[1], credentials) {
+        (0, _) => {}
+        (2, Some((username, password))) => {
+            if username.len() > 255 || password.len() > 255 {
+                return Err(proxy_error("SOCKS5 credentials are too long"));
+            }
+            let mut request = vec![1, username.len() as u8];
+            request.extend_from_slice(username.as_bytes());
+            request.push(password.len() as u8);
+            request.extend_from_slice(password.as_bytes());
+            stream.write_all(&request)?;
+            stream.read_exact(&mut reply)?;
+            if reply[0] != 1 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid SOCKS5 authentication reply"));
+            }
+            if reply[1] != 0 {
+                return Err(proxy_error("SOCKS5 authentication failed"));
+            }
+        }
+        _ => return Err(proxy_error("no acceptable SOCKS5 authentication method")),
+    }
+
+    let mut request = vec![5, 1, 0];
+    match host.parse() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        // The proxy resolves the name.
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(proxy_error("hostname is too long for SOCKS5"));
+            }
+            request.push(3);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[0] != 5 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid SOCKS5 reply"));
+    }
+    if reply[1] != 0 {
+        let reason = match reply[1] {
+            1 => "general failure",
+            2 => "connection not allowed by ruleset",
+            3 => "network unreachable",
+            4 => "host unreachable",
+            5 => "connection refused",
+            6 => "TTL expired",
+            7 => "command not supported",
+            8 => "address type not supported",
+            _ => "unknown error",
+        };
+        return Err(proxy_error(format!("SOCKS5 proxy could not connect: {}", reason)));
+    }
+    // Skip the address bound by the proxy, and its port.
+    let len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => {
+            let mut len = [0];
+            stream.read_exact(&mut len)?;
+            len[0] as usize
+        }
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid SOCKS5 reply")),
+    };
+    let mut bound = vec![0; len + 2];
+    stream.read_exact(&mut bound)
+}
+
+fn http_connect(stream: &mut TcpStream, target: &str, credentials: Option<&(String, String)>) -> io::Result<()> {
+    let mut request = format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n", target, target);
+    if let Some((username, password)) = credentials {
+        let token = base64(format!("{}:{}", username, password).as_bytes());
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+
+    // Read byte by byte, what follows the headers belongs to the server.
+    let mut response = Vec::new();
+    let mut byte = [0];
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "HTTP proxy closed the connection"));
+        }
+        response.push(byte[0]);
+        if response.len() > 8192 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "HTTP proxy response is too long"));
+        }
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status = response.lines().next().unwrap_or("");
+    match status.split(' ').nth(1) {
+        Some(code) if code.starts_with('2') && status.starts_with("HTTP/") => Ok(()),
+        _ => Err(proxy_error(format!("HTTP proxy could not connect: {}", status))),
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut res = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let bits = (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize;
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                res.push(ALPHABET[bits >> (18 - 6 * idx) & 0x3f] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+// Accept a single connection on a local port, handled by the closure.
+#[cfg(test)]
+fn stand_in<F: FnOnce(TcpStream) + Send + 'static>(handler: F) -> String {
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    ::std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        handler(stream);
+    });
+    address
+}
+
+#[test]
+fn test_split_address() {
+    assert_eq!(split_address("irc.example.com:6667").unwrap(), ("irc.example.com", 6667));
+    assert_eq!(split_address("[::1]:6697").unwrap(), ("::1", 6697));
+    assert!(split_address("irc.example.com").is_err());
+}
+
+#[test]
+fn test_base64() {
+    assert_eq!(base64(b"bob:hunter2"), "Ym9iOmh1bnRlcjI=");
+    assert_eq!(base64(b"ab"), "YWI=");
+    assert_eq!(base64(b"abc"), "YWJj");
+}
+
+#[test]
+fn test_socks5() {
+    let address = stand_in(|mut stream| {
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [5, 2, 0, 2]);
+        stream.write_all(&[5, 2]).unwrap();
+        let mut auth = [0; 13];
+        stream.read_exact(&mut auth).unwrap();
+        assert_eq!(&auth, b"\x01\x03bob\x07hunter2");
+        stream.write_all(&[1, 0]).unwrap();
+        // The name is given to the proxy.
+        let mut request = [0; 5 + 17 + 2];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(&request[..], &b"\x05\x01\x00\x03\x11irc.example.onion\x1a\x0b"[..]);
+        stream.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).unwrap();
+        stream.write_all(b":server 001 bob :Welcome\r\n").unwrap();
+    });
+    let proxy = Proxy::socks5(address).credentials("bob", "hunter2");
+    let mut stream = proxy.connect("irc.example.onion:6667").unwrap();
+    assert_eq!(stream.read_timeout().unwrap(), None);
+    let mut welcome = String::new();
+    stream.read_to_string(&mut welcome).unwrap();
+    assert_eq!(welcome, ":server 001 bob :Welcome\r\n");
+
+    let address = stand_in(|mut stream| {
+        let mut buf = [0; 3];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&[5, 0]).unwrap();
+        let mut request = [0; 10];
+        stream.read_exact(&mut request).unwrap();
+        stream.write_all(&[5, 5, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+    });
+    let err = Proxy::socks5(address).connect("127.0.0.1:6667").err().unwrap();
+    assert_eq!(err.to_string(), "SOCKS5 proxy could not connect: connection refused");
+
+    // Replies with the wrong version are rejected.
+    let address = stand_in(|mut stream| {
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&[5, 2]).unwrap();
+        let mut auth = [0; 5];
+        stream.read_exact(&mut auth).unwrap();
+        stream.write_all(&[5, 0]).unwrap();
+    });
+    let err = Proxy::socks5(address).credentials("a", "b").connect("127.0.0.1:6667").err().unwrap();
+    assert_eq!(err.to_string(), "invalid SOCKS5 authentication reply");
+
+    let address = stand_in(|mut stream| {
+        let mut buf = [0; 3];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&[5, 0]).unwrap();
+        let mut request = [0; 10];
+        stream.read_exact(&mut request).unwrap();
+        stream.write_all(&[4, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+    });
+    let err = Proxy::socks5(address).connect("127.0.0.1:6667").err().unwrap();
+    assert_eq!(err.to_string(), "invalid SOCKS5 reply");
+}
+
+#[test]
+fn test_handshake_timeout() {
+    use std::time::Instant;
+
+    // The proxy accepts the connection but never answers.
+    for proxy in [Proxy::socks5(stand_in(|_stream| ::std::thread::sleep(Duration::from_secs(5)))),
+                  Proxy::http_connect(stand_in(|_stream| ::std::thread::sleep(Duration::from_secs(5))))].iter() {
+        let start = Instant::now();
+        assert!(proxy.connect_timeout("irc.example.com:6667", Duration::from_millis(200)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
+
+#[test]
+fn test_http_connect() {
+    use std::io::{BufRead, BufReader};
+
+    let address = stand_in(|stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = String::new();
+        while !request.ends_with("\r\n\r\n") {
+            reader.read_line(&mut request).unwrap();
+        }
+        assert_eq!(request, "CONNECT irc.example.com:6667 HTTP/1.1\r\nHost: irc.example.com:6667\r\n\
+                             Proxy-Authorization: Basic Ym9iOmh1bnRlcjI=\r\n\r\n");
+        (&stream).write_all(b"HTTP/1.1 200 Connection established\r\n\r\n:server 001 bob :Welcome\r\n").unwrap();
+    });
+    let proxy = Proxy::http_connect(address).credentials("bob", "hunter2");
+    let mut stream = proxy.connect("irc.example.com:6667").unwrap();
+    let mut welcome = String::new();
+    stream.read_to_string(&mut welcome).unwrap();
+    assert_eq!(welcome, ":server 001 bob :Welcome\r\n");
+
+    let address = stand_in(|stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        while line != "\r\n" {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+        }
+        (&stream).write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n").unwrap();
+    });
+    let err = Proxy::http_connect(address).connect("irc.example.com:6667").err().unwrap();
+    assert_eq!(err.to_string(), "HTTP proxy could not connect: HTTP/1.1 407 Proxy Authentication Required");
+}
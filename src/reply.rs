@@ -0,0 +1,376 @@
+use std::convert::TryFrom;
+
+use code::Code;
+use command::{check_args, DecodeError};
+use message::Message;
+
+// Membership prefixes sent in NAMES replies when the server does not tell otherwise.
+const DEFAULT_PREFIXES: &str = "~&@%+";
+
+// Make sure the message has the given code and enough arguments.
+//
+// The first argument of a numeric reply is always the client's nickname,
+// it is included in the count.
+fn check_reply(msg: &Message, code: &Code, expected: usize) -> Result<(), DecodeError> {
+    if msg.code != *code {
+        return Err(DecodeError::UnexpectedCode(msg.code.clone()));
+    }
+    check_args(msg, expected)
+}
+
+fn parse_number<T: ::std::str::FromStr>(msg: &Message, idx: usize) -> Result<T, DecodeError> {
+    msg.args[idx].parse().map_err(|_| DecodeError::InvalidArg(idx))
+}
+
+/// RPL_WELCOME, the first reply after registration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplWelcome {
+    /// Nickname the server registered the client with.
+    pub nickname: String,
+    /// Welcome message.
+    pub message: String,
+}
+
+impl<'a> TryFrom<&'a Message> for RplWelcome {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplWelcome, DecodeError> {
+        check_reply(msg, &Code::RplWelcome, 2)?;
+        Ok(RplWelcome {
+            nickname: msg.args[0].clone(),
+            message: msg.args[1].clone(),
+        })
+    }
+}
+
+/// Visibility of a channel in a NAMES reply.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChannelType {
+    /// Public channel, `=`.
+    Public,
+    /// Secret channel, `@`.
+    Secret,
+    /// Private channel, `*`.
+    Private,
+}
+
+/// Nickname in a NAMES reply, with its membership prefixes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NamesNick {
+    /// Membership prefixes, such as `@` or `+`. There can be more than one with multi-prefix.
+    pub prefixes: String,
+    /// Nickname, or full `nick!user@host` with userhost-in-names.
+    pub nickname: String,
+}
+
+impl NamesNick {
+
+    /// Split the membership prefixes from a nickname.
+    ///
+    /// The `prefixes` are the symbols from the `PREFIX` ISUPPORT token.
+    pub fn parse(text: &str, prefixes: &str) -> NamesNick {
+        let nickname = text.trim_start_matches(|c| prefixes.contains(c));
+        NamesNick {
+            prefixes: text[..text.len() - nickname.len()].into(),
+            nickname: nickname.into(),
+        }
+    }
+
+}
+
+/// RPL_NAMREPLY, a part of the member list of a channel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplNamReply {
+    /// Visibility of the channel.
+    pub channel_type: ChannelType,
+    /// Channel.
+    pub channel: String,
+    /// Members of the channel.
+    pub nicks: Vec<NamesNick>,
+}
+
+impl RplNamReply {
+
+    /// Decode the reply using the membership prefixes advertised by the server.
+    pub fn decode(msg: &Message, prefixes: &str) -> Result<RplNamReply, DecodeError> {
+        check_reply(msg, &Code::RplNamreply, 4)?;
+        let channel_type = match &msg.args[1][..] {
+            "=" => ChannelType::Public,
+            "@" => ChannelType::Secret,
+            "*" => ChannelType::Private,
+            _ => return Err(DecodeError::InvalidArg(1)),
+        };
+        Ok(RplNamReply {
+            channel_type,
+            channel: msg.args[2].clone(),
+            nicks: msg.args[3].split(' ')
+                                .filter(|nick| !nick.is_empty())
+                                .map(|nick| NamesNick::parse(nick, prefixes))
+                                .collect(),
+        })
+    }
+
+}
+
+/// Uses the `~&@%+` prefixes, see `RplNamReply::decode` to use others.
+impl<'a> TryFrom<&'a Message> for RplNamReply {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplNamReply, DecodeError> {
+        RplNamReply::decode(msg, DEFAULT_PREFIXES)
+    }
+}
+
+/// RPL_WHOREPLY, a user matching a WHO query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplWhoReply {
+    /// Channel, or `*` if none.
+    pub channel: String,
+    /// Username
+    pub username: String,
+    /// Hostname
+    pub hostname: String,
+    /// Server the user is connected to.
+    pub server: String,
+    /// Nickname
+    pub nickname: String,
+    /// Flags, `H` or `G` for here or gone, followed by `*` for operators and membership prefixes.
+    pub flags: String,
+    /// Amount of hops to the user's server.
+    pub hopcount: u32,
+    /// Real name.
+    pub realname: String,
+}
+
+impl<'a> TryFrom<&'a Message> for RplWhoReply {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplWhoReply, DecodeError> {
+        check_reply(msg, &Code::RplWhoreply, 8)?;
+        // The last argument is "<hopcount> <realname>".
+        let last = &msg.args[7];
+        let (hopcount, realname) = match last.find(' ') {
+            Some(idx) => (&last[..idx], &last[idx + 1..]),
+            None => (&last[..], ""),
+        };
+        Ok(RplWhoReply {
+            channel: msg.args[1].clone(),
+            username: msg.args[2].clone(),
+            hostname: msg.args[3].clone(),
+            server: msg.args[4].clone(),
+            nickname: msg.args[5].clone(),
+            flags: msg.args[6].clone(),
+            hopcount: hopcount.parse().map_err(|_| DecodeError::InvalidArg(7))?,
+            realname: realname.into(),
+        })
+    }
+}
+
+/// RPL_TOPIC, the topic of a channel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplTopic {
+    /// Channel.
+    pub channel: String,
+    /// Topic.
+    pub topic: String,
+}
+
+impl<'a> TryFrom<&'a Message> for RplTopic {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplTopic, DecodeError> {
+        check_reply(msg, &Code::RplTopic, 3)?;
+        Ok(RplTopic {
+            channel: msg.args[1].clone(),
+            topic: msg.args[2].clone(),
+        })
+    }
+}
+
+/// RPL_TOPICWHOTIME (333), who set the topic of a channel and when.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplTopicWhoTime {
+    /// Channel.
+    pub channel: String,
+    /// Nickname or full prefix of the user who set the topic.
+    pub set_by: String,
+    /// When the topic was set, in seconds since the epoch.
+    pub set_at: u64,
+}
+
+impl<'a> TryFrom<&'a Message> for RplTopicWhoTime {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplTopicWhoTime, DecodeError> {
+        check_reply(msg, &Code::RplTopicwhotime, 4)?;
+        Ok(RplTopicWhoTime {
+            channel: msg.args[1].clone(),
+            set_by: msg.args[2].clone(),
+            set_at: parse_number(msg, 3)?,
+        })
+    }
+}
+
+/// RPL_WHOISUSER, the first reply to a WHOIS query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplWhoisUser {
+    /// Nickname
+    pub nickname: String,
+    /// Username
+    pub username: String,
+    /// Hostname
+    pub hostname: String,
+    /// Real name.
+    pub realname: String,
+}
+
+impl<'a> TryFrom<&'a Message> for RplWhoisUser {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplWhoisUser, DecodeError> {
+        check_reply(msg, &Code::RplWhoisuser, 6)?;
+        Ok(RplWhoisUser {
+            nickname: msg.args[1].clone(),
+            username: msg.args[2].clone(),
+            hostname: msg.args[3].clone(),
+            realname: msg.args[5].clone(),
+        })
+    }
+}
+
+/// RPL_CHANNELMODEIS, the modes of a channel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplChannelModeIs {
+    /// Channel.
+    pub channel: String,
+    /// Mode string and its parameters, unparsed.
+    pub modes: Vec<String>,
+}
+
+impl<'a> TryFrom<&'a Message> for RplChannelModeIs {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplChannelModeIs, DecodeError> {
+        check_reply(msg, &Code::RplChannelmodeis, 3)?;
+        Ok(RplChannelModeIs {
+            channel: msg.args[1].clone(),
+            modes: msg.args[2..].to_vec(),
+        })
+    }
+}
+
+/// RPL_BANLIST, an entry of the ban list of a channel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplBanList {
+    /// Channel.
+    pub channel: String,
+    /// Ban mask.
+    pub mask: String,
+    /// Who set the ban, if the server tells.
+    pub set_by: Option<String>,
+    /// When the ban was set, in seconds since the epoch, if the server tells.
+    pub set_at: Option<u64>,
+}
+
+impl<'a> TryFrom<&'a Message> for RplBanList {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplBanList, DecodeError> {
+        check_reply(msg, &Code::RplBanlist, 3)?;
+        let set_at = match msg.args.get(4) {
+            Some(_) => Some(parse_number(msg, 4)?),
+            None => None,
+        };
+        Ok(RplBanList {
+            channel: msg.args[1].clone(),
+            mask: msg.args[2].clone(),
+            set_by: msg.args.get(3).cloned(),
+            set_at,
+        })
+    }
+}
+
+/// RPL_LIST, a channel matching a LIST query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RplListReply {
+    /// Channel.
+    pub channel: String,
+    /// Amount of visible users.
+    pub visible: u32,
+    /// Topic.
+    pub topic: String,
+}
+
+impl<'a> TryFrom<&'a Message> for RplListReply {
+    type Error = DecodeError;
+
+    fn try_from(msg: &'a Message) -> Result<RplListReply, DecodeError> {
+        check_reply(msg, &Code::RplList, 3)?;
+        Ok(RplListReply {
+            channel: msg.args[1].clone(),
+            visible: parse_number(msg, 2)?,
+            topic: msg.args.get(3).cloned().unwrap_or_default(),
+        })
+    }
+}
+
+#[test]
+fn test_welcome() {
+    let msg = Message::parse(":irc.example.com 001 bob :Welcome bob").unwrap();
+    assert_eq!(RplWelcome::try_from(&msg), Ok(RplWelcome {
+        nickname: "bob".into(),
+        message: "Welcome bob".into(),
+    }));
+}
+
+#[test]
+fn test_namreply() {
+    let msg = Message::parse(":irc.example.com 353 bob @ #chan :@+op +voice user ").unwrap();
+    let reply = RplNamReply::try_from(&msg).unwrap();
+    assert_eq!(reply.channel_type, ChannelType::Secret);
+    assert_eq!(reply.channel, "#chan");
+    assert_eq!(reply.nicks, vec![
+        NamesNick { prefixes: "@+".into(), nickname: "op".into() },
+        NamesNick { prefixes: "+".into(), nickname: "voice".into() },
+        NamesNick { prefixes: "".into(), nickname: "user".into() },
+    ]);
+}
+
+#[test]
+fn test_whoreply() {
+    let msg = Message::parse(":irc.example.com 352 bob #chan ~al al.com irc.example.com al H@ :0 Al Bundy").unwrap();
+    let reply = RplWhoReply::try_from(&msg).unwrap();
+    assert_eq!(reply.nickname, "al");
+    assert_eq!(reply.flags, "H@");
+    assert_eq!(reply.hopcount, 0);
+    assert_eq!(reply.realname, "Al Bundy");
+}
+
+#[test]
+fn test_topic_who_time() {
+    let msg = Message::parse(":irc.example.com 333 bob #chan al!al@al.com 1453723200").unwrap();
+    assert_eq!(RplTopicWhoTime::try_from(&msg), Ok(RplTopicWhoTime {
+        channel: "#chan".into(),
+        set_by: "al!al@al.com".into(),
+        set_at: 1453723200,
+    }));
+}
+
+#[test]
+fn test_banlist() {
+    let msg = Message::parse(":irc.example.com 367 bob #chan *!*@bad.com").unwrap();
+    let reply = RplBanList::try_from(&msg).unwrap();
+    assert_eq!(reply.mask, "*!*@bad.com");
+    assert_eq!(reply.set_by, None);
+    assert_eq!(reply.set_at, None);
+}
+
+#[test]
+fn test_errors() {
+    let msg = Message::parse(":irc.example.com 322 bob #chan many :topic").unwrap();
+    assert_eq!(RplListReply::try_from(&msg), Err(DecodeError::InvalidArg(2)));
+    let msg = Message::parse(":irc.example.com 332 bob").unwrap();
+    assert_eq!(RplTopic::try_from(&msg), Err(DecodeError::NotEnoughArgs { expected: 3, found: 1 }));
+    let msg = Message::parse(":irc.example.com 332 bob #chan :topic").unwrap();
+    assert_eq!(RplWelcome::try_from(&msg), Err(DecodeError::UnexpectedCode(Code::RplTopic)));
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use casemap::CaseMapping;
+use code::Code;
+use connection::Event;
+use dialect::Dialect;
+use message::Message;
+use mode::ChannelModes;
+
+/// Features advertised by the server with `RPL_ISUPPORT` (005) replies.
+///
+/// Feed it the events received from the connection, like the `ActivityMonitor`.
+/// It collects the tokens of every `RPL_ISUPPORT` reply, and forgets them when
+/// the connection is restored, since the server might be a different one.
+/// The dialect of the server is detected from `RPL_YOURHOST` and `RPL_MYINFO`.
+///
+/// Typed accessors return the defaults from the specification when the server
+/// does not advertise a token. Unknown tokens are kept and available via `get`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ServerInfo {
+    tokens: HashMap<String, String>,
+    dialect: Dialect,
+}
+
+impl ServerInfo {
+
+    /// Create an empty `ServerInfo`.
+    pub fn new() -> ServerInfo {
+        ServerInfo::default()
+    }
+
+    /// Give an event received from the connection.
+    ///
+    /// `RPL_ISUPPORT` replies are collected and `Event::Reconnected` resets everything.
+    pub fn feed(&mut self, event: &Event) {
+        match *event {
+            Event::Reconnected => self.clear(),
+            Event::Message(ref msg) => self.feed_message(msg),
+            _ => {}
+        }
+    }
+
+    /// Collect the tokens of the message, if it is a `RPL_ISUPPORT` reply,
+    /// or the dialect if it is a `RPL_YOURHOST` or `RPL_MYINFO` reply.
+    pub fn feed_message(&mut self, msg: &Message) {
+        if let Some(dialect) = Dialect::from_message(msg) {
+            // RPL_MYINFO might not know a version that RPL_YOURHOST knew.
+            if dialect != Dialect::Generic {
+                self.dialect = dialect;
+            }
+            return;
+        }
+        if msg.code != Code::RplIsupport || msg.args.len() < 2 {
+            return;
+        }
+        // The first argument is the client's nickname, the last one is a human readable text.
+        for token in &msg.args[1..msg.args.len() - 1] {
+            if let Some(name) = token.strip_prefix('-') {
+                self.tokens.remove(name);
+                continue;
+            }
+            match token.find('=') {
+                Some(idx) => self.tokens.insert(token[..idx].into(), unescape_value(&token[idx + 1..])),
+                None => self.tokens.insert(token.clone(), String::new()),
+            };
+        }
+    }
+
+    /// Forget every token and the dialect.
+    pub fn clear(&mut self) {
+        self.tokens.clear();
+        self.dialect = Dialect::Generic;
+    }
+
+    /// Dialect of the server, `Dialect::Generic` until it is detected.
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Get the value of a token, an empty string if it has none.
+    ///
+    /// `None` is returned if the server does not advertise the token.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.tokens.get(name).map(|value| &value[..])
+    }
+
+    /// Check if the server advertises the token.
+    pub fn has(&self, name: &str) -> bool {
+        self.tokens.contains_key(name)
+    }
+
+    /// Iterate over every token and its value.
+    pub fn tokens(&self) -> ::std::collections::hash_map::Iter<'_, String, String> {
+        self.tokens.iter()
+    }
+
+    fn get_number(&self, name: &str) -> Option<usize> {
+        self.get(name).and_then(|value| value.parse().ok())
+    }
+
+    /// `NETWORK`, the name of the network.
+    pub fn network(&self) -> Option<&str> {
+        self.get("NETWORK").filter(|value| !value.is_empty())
+    }
+
+    /// `CHANTYPES`, the channel prefixes. Defaults to `#&`.
+    pub fn chantypes(&self) -> &str {
+        self.get("CHANTYPES").unwrap_or("#&")
+    }
+
+    /// Check if the name is a channel, according to `CHANTYPES`.
+    pub fn is_channel(&self, name: &str) -> bool {
+        name.chars().next().is_some_and(|c| self.chantypes().contains(c))
+    }
+
+    /// `PREFIX`, the membership modes and their symbols, in order of rank. Defaults to `(ov)@+`.
+    pub fn prefix(&self) -> Vec<(char, char)> {
+        let value = self.get("PREFIX").unwrap_or("(ov)@+");
+        let (modes, symbols) = match (value.strip_prefix('('), value.find(')')) {
+            (Some(_), Some(idx)) => (&value[1..idx], &value[idx + 1..]),
+            _ => return Vec::new(),
+        };
+        modes.chars().zip(symbols.chars()).collect()
+    }
+
+    /// The membership symbols from `PREFIX`, such as `@+`.
+    pub fn prefix_symbols(&self) -> String {
+        self.prefix().into_iter().map(|(_, symbol)| symbol).collect()
+    }
+
+    /// `CHANMODES` and `PREFIX`, the classes of the channel modes.
+    ///
+    /// Each token defaults on its own to the values of `ChannelModes::default`.
+    pub fn channel_modes(&self) -> ChannelModes {
+        ChannelModes::from_tokens(self.get("CHANMODES").unwrap_or("beI,k,l,imnpst"),
+                                  self.get("PREFIX").unwrap_or("(ov)@+"))
+    }
+
+    /// `CASEMAPPING`, how names are compared. Defaults to `rfc1459`.
+    pub fn case_mapping(&self) -> CaseMapping {
+        self.get("CASEMAPPING").and_then(CaseMapping::from_token).unwrap_or_default()
+    }
+
+    /// `NICKLEN`, the maximum length of a nickname.
+    pub fn nicklen(&self) -> Option<usize> {
+        self.get_number("NICKLEN")
+    }
+
+    /// `CHANNELLEN`, the maximum length of a channel name.
+    pub fn channellen(&self) -> Option<usize> {
+        self.get_number("CHANNELLEN")
+    }
+
+    /// `TOPICLEN`, the maximum length of a topic.
+    pub fn topiclen(&self) -> Option<usize> {
+        self.get_number("TOPICLEN")
+    }
+
+    /// `MODES`, the maximum amount of modes with a parameter in a single MODE command.
+    ///
+    /// Defaults to 3 when not advertised, `None` means there is no limit.
+    pub fn modes(&self) -> Option<usize> {
+        match self.get("MODES") {
+            None => Some(3),
+            Some(value) => value.parse().ok(),
+        }
+    }
+
+    /// `MAXLIST`, the maximum amount of entries of list modes, such as `[("beI", 100)]`.
+    pub fn maxlist(&self) -> Vec<(String, usize)> {
+        self.get("MAXLIST").map(|value| {
+            value.split(',').filter_map(|item| {
+                let idx = item.find(':')?;
+                Some((item[..idx].into(), item[idx + 1..].parse().ok()?))
+            }).collect()
+        }).unwrap_or_default()
+    }
+
+    /// `TARGMAX`, the maximum amount of targets of commands. `None` means no limit.
+    pub fn targmax(&self) -> Vec<(String, Option<usize>)> {
+        self.get("TARGMAX").map(|value| {
+            value.split(',').filter_map(|item| {
+                let idx = item.find(':')?;
+                Some((item[..idx].into(), item[idx + 1..].parse().ok()))
+            }).collect()
+        }).unwrap_or_default()
+    }
+
+    /// `STATUSMSG`, the membership symbols which can prefix a channel to message its members.
+    pub fn statusmsg(&self) -> &str {
+        self.get("STATUSMSG").unwrap_or("")
+    }
+
+    /// `EXCEPTS`, the ban exception mode. Defaults to `e` when advertised without a value.
+    pub fn excepts(&self) -> Option<char> {
+        self.get("EXCEPTS").map(|value| value.chars().next().unwrap_or('e'))
+    }
+
+    /// `INVEX`, the invite exception mode. Defaults to `I` when advertised without a value.
+    pub fn invex(&self) -> Option<char> {
+        self.get("INVEX").map(|value| value.chars().next().unwrap_or('I'))
+    }
+
+    /// `MONITOR`, whether the MONITOR command is supported.
+    pub fn monitor(&self) -> bool {
+        self.has("MONITOR")
+    }
+
+    /// `MONITOR`, the maximum amount of monitored nicknames, if limited.
+    pub fn monitor_limit(&self) -> Option<usize> {
+        self.get_number("MONITOR")
+    }
+
+    /// `UTF8ONLY`, whether the server only accepts UTF-8.
+    pub fn utf8only(&self) -> bool {
+        self.has("UTF8ONLY")
+    }
+
+}
+
+// Values can contain "\xHH" escapes, for spaces and such.
+fn unescape_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'\\' && idx + 4 <= bytes.len() && bytes[idx + 1] == b'x' {
+            let hex = ::std::str::from_utf8(&bytes[idx + 2..idx + 4]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                res.push(byte);
+                idx += 4;
+                continue;
+            }
+        }
+        res.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+#[cfg(test)]
+fn isupport(line: &str) -> Event {
+    Event::Message(Message::parse(line).unwrap())
+}
+
+#[test]
+fn test_feed() {
+    let mut info = ServerInfo::new();
+    info.feed(&isupport(":irc.example.com 005 bob NETWORK=Example\\x20Net CHANTYPES=# PREFIX=(qaohv)~&@%+ \
+                         CHANMODES=beI,k,l,imnpst CASEMAPPING=ascii NICKLEN=30 EXCEPTS INVEX=J \
+                         :are supported by this server"));
+    info.feed(&isupport(":irc.example.com 005 bob MAXLIST=beI:100 TARGMAX=PRIVMSG:4,JOIN: MONITOR=100 \
+                         UTF8ONLY STATUSMSG=@+ MODES=4 FOO=bar :are supported by this server"));
+
+    assert_eq!(info.network(), Some("Example Net"));
+    assert_eq!(info.chantypes(), "#");
+    assert!(info.is_channel("#chan"));
+    assert!(!info.is_channel("&chan"));
+    assert_eq!(info.prefix(), vec![('q', '~'), ('a', '&'), ('o', '@'), ('h', '%'), ('v', '+')]);
+    assert_eq!(info.prefix_symbols(), "~&@%+");
+    assert_eq!(info.channel_modes().prefix, "qaohv");
+    assert_eq!(info.case_mapping(), CaseMapping::Ascii);
+    assert_eq!(info.nicklen(), Some(30));
+    assert_eq!(info.excepts(), Some('e'));
+    assert_eq!(info.invex(), Some('J'));
+    assert_eq!(info.maxlist(), vec![("beI".to_string(), 100)]);
+    assert_eq!(info.targmax(), vec![("PRIVMSG".to_string(), Some(4)), ("JOIN".to_string(), None)]);
+    assert!(info.monitor());
+    assert_eq!(info.monitor_limit(), Some(100));
+    assert!(info.utf8only());
+    assert_eq!(info.statusmsg(), "@+");
+    assert_eq!(info.modes(), Some(4));
+    assert_eq!(info.get("FOO"), Some("bar"));
+}
+
+#[test]
+fn test_negation() {
+    let mut info = ServerInfo::new();
+    info.feed(&isupport(":irc.example.com 005 bob EXCEPTS NICKLEN=30 :are supported by this server"));
+    info.feed(&isupport(":irc.example.com 005 bob -EXCEPTS :are supported by this server"));
+    assert_eq!(info.excepts(), None);
+    assert_eq!(info.nicklen(), Some(30));
+}
+
+#[test]
+fn test_defaults_and_reset() {
+    let mut info = ServerInfo::new();
+    assert_eq!(info.chantypes(), "#&");
+    assert_eq!(info.prefix(), vec![('o', '@'), ('v', '+')]);
+    assert_eq!(info.case_mapping(), CaseMapping::Rfc1459);
+    assert_eq!(info.modes(), Some(3));
+    assert_eq!(info.channel_modes(), ChannelModes::default());
+
+    // PREFIX is used even without CHANMODES.
+    info.feed(&isupport(":irc.example.com 005 bob PREFIX=(qov)~@+ :are supported by this server"));
+    assert_eq!(info.channel_modes().prefix, "qov");
+    assert_eq!(info.channel_modes().list, "beI");
+
+    info.feed(&isupport(":irc.example.com 005 bob CASEMAPPING=ascii :are supported by this server"));
+    info.feed(&Event::Reconnected);
+    assert_eq!(info.case_mapping(), CaseMapping::Rfc1459);
+}
+
+#[test]
+fn test_dialect() {
+    let mut info = ServerInfo::new();
+    info.feed(&isupport(":irc.example.com 002 bob :Your host is irc.example.com, running version InspIRCd-3"));
+    info.feed(&isupport(":irc.example.com 004 bob irc.example.com 3.16 iosw biklmnopstv"));
+    assert_eq!(info.dialect(), Dialect::InspIRCd);
+    info.feed(&Event::Reconnected);
+    assert_eq!(info.dialect(), Dialect::Generic);
+}
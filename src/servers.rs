@@ -0,0 +1,240 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use code::Code;
+use message::Message;
+use proxy::Proxy;
+#[cfg(feature = "tls")]
+use tls::{TlsConnector, TlsSettings};
+use transport::{self, Connection, Transport, DEFAULT_TIMEOUT};
+
+/// A server of the network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Server {
+    /// Hostname or IP address.
+    pub host: String,
+    /// Port, usually 6667, or 6697 with TLS.
+    pub port: u16,
+    /// Whether to connect with TLS. It requires the `tls` feature.
+    pub tls: bool,
+    /// Password sent with `PASS` as soon as the connection is made.
+    pub password: Option<String>,
+}
+
+impl Server {
+
+    /// Create a server without TLS nor password.
+    pub fn new<H: Into<String>>(host: H, port: u16) -> Server {
+        Server {
+            host: host.into(),
+            port,
+            tls: false,
+            password: None,
+        }
+    }
+
+    /// Connect with TLS.
+    pub fn tls(mut self) -> Server {
+        self.tls = true;
+        self
+    }
+
+    /// Send the password with `PASS`.
+    ///
+    /// Connecting fails if the password contains a CR, LF or NUL character.
+    pub fn password<P: Into<String>>(mut self, password: P) -> Server {
+        self.password = Some(password.into());
+        self
+    }
+
+}
+
+/// Formats the address of the server, such as `irc.example.com:6697`.
+impl fmt::Display for Server {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+
+}
+
+/// Connects to the servers of a list, moving to the next one when a server fails.
+///
+/// Every connection attempt goes through the list once, starting with the server
+/// of the last connection, until a server answers. The addresses of a server are
+/// all tried like `TcpTransport` does, with the same timeout, which also bounds
+/// the proxy and TLS handshakes.
+///
+/// TLS servers use the default `TlsSettings` unless others are given.
+#[derive(Clone)]
+pub struct ServerList {
+    servers: Vec<Server>,
+    current: usize,
+    timeout: Duration,
+    proxy: Option<Proxy>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConnector>,
+}
+
+impl ServerList {
+
+    /// Create a list starting with the first server.
+    pub fn new(servers: Vec<Server>) -> ServerList {
+        ServerList {
+            servers,
+            current: 0,
+            timeout: DEFAULT_TIMEOUT,
+            proxy: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Set the time given to a connection attempt to an address, and to each read
+    /// and write of the handshakes.
+    pub fn timeout(mut self, timeout: Duration) -> ServerList {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Connect through the proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> ServerList {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Use the settings for the TLS servers, available with the `tls` feature.
+    ///
+    /// An error is returned if the certificates or the key are invalid.
+    #[cfg(feature = "tls")]
+    pub fn tls_settings(mut self, tls: &TlsSettings) -> io::Result<ServerList> {
+        self.tls = Some(tls.connector()?);
+        Ok(self)
+    }
+
+    /// The server of the last connection, or the one to try next.
+    pub fn current(&self) -> Option<&Server> {
+        self.servers.get(self.current)
+    }
+
+    fn connect_server(&mut self, server: &Server) -> io::Result<Connection> {
+        let pass = match server.password {
+            Some(ref password) => {
+                let msg = Message::builder(Code::Pass).trailing(password.as_str()).build()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid password: {}", err)))?;
+                Some(msg.to_line())
+            }
+            None => None,
+        };
+
+        let address = server.to_string();
+        let stream = match self.proxy {
+            Some(ref proxy) => proxy.connect_timeout(&address, self.timeout)?,
+            None => transport::connect_timeout((&server.host[..], server.port), self.timeout)?,
+        };
+        let sock = stream.try_clone()?;
+        transport::set_timeouts(&sock, Some(self.timeout))?;
+
+        let (reader, mut writer): Connection = if server.tls {
+            self.open_tls(stream, &address)?
+        } else {
+            (Box::new(stream.try_clone()?), Box::new(stream))
+        };
+
+        if let Some(pass) = pass {
+            writer.write_all(pass.as_bytes())?;
+        }
+        transport::set_timeouts(&sock, None)?;
+        Ok((reader, writer))
+    }
+
+    #[cfg(feature = "tls")]
+    fn open_tls(&mut self, stream: ::std::net::TcpStream, address: &str) -> io::Result<Connection> {
+        if self.tls.is_none() {
+            self.tls = Some(TlsSettings::new().connector()?);
+        }
+        let tls = self.tls.as_ref().unwrap();
+        let (reader, writer) = tls.open(stream, address)?;
+        Ok((reader, Box::new(writer)))
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn open_tls(&mut self, _: ::std::net::TcpStream, address: &str) -> io::Result<Connection> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} requires the tls feature", address)))
+    }
+
+}
+
+impl Transport for ServerList {
+
+    fn connect(&mut self) -> io::Result<Connection> {
+        let mut errors = Vec::new();
+        let mut kind = io::ErrorKind::InvalidInput;
+        for _ in 0..self.servers.len() {
+            let server = self.servers[self.current].clone();
+            match self.connect_server(&server) {
+                Ok(conn) => return Ok(conn),
+                Err(err) => {
+                    errors.push(format!("{}: {}", server, err));
+                    kind = err.kind();
+                    self.current = (self.current + 1) % self.servers.len();
+                }
+            }
+        }
+        if errors.is_empty() {
+            errors.push("no server to connect to".into());
+        }
+        Err(io::Error::new(kind, errors.join(", ")))
+    }
+
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(Server::new("irc.example.com", 6697).to_string(), "irc.example.com:6697");
+    assert_eq!(Server::new("::1", 6667).to_string(), "[::1]:6667");
+}
+
+#[test]
+fn test_rotation() {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    // Nothing listens on the port of a dropped listener.
+    let dead = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let alive = listener.local_addr().unwrap().port();
+
+    let mut servers = ServerList::new(vec![
+        Server::new("127.0.0.1", dead),
+        Server::new("localhost", alive).password("hunter2"),
+    ]).timeout(Duration::from_secs(5));
+    assert!(servers.connect().is_ok());
+    assert_eq!(servers.current().map(|server| server.port), Some(alive));
+    let (stream, _) = listener.accept().unwrap();
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    assert_eq!(line, "PASS :hunter2\r\n");
+
+    // The working server is tried first on the next connection.
+    assert!(servers.connect().is_ok());
+    assert!(listener.accept().is_ok());
+
+    drop(listener);
+    let err = servers.connect().err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+    assert!(err.to_string().starts_with(&format!("localhost:{}: ", alive)));
+    assert!(ServerList::new(Vec::new()).connect().is_err());
+}
+
+#[test]
+fn test_invalid_password() {
+    let mut servers = ServerList::new(vec![Server::new("127.0.0.1", 6667).password("hunter2\r\nQUIT")]);
+    let err = servers.connect().err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,270 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use builder::BuildError;
+use charset::EncodePolicy;
+use code::Code;
+use formatting::{FormattedText, Style};
+use message::{Message, PrefixUser, Tag, MAX_CLIENT_TAGS_LENGTH, MAX_LINE_LENGTH};
+
+// Room kept for the parts of our prefix that we do not know, `!` and `@` included.
+const USERNAME_RESERVE: usize = 1 + 10;
+const HOSTNAME_RESERVE: usize = 1 + 63;
+
+/// Splits long text into `PRIVMSG` or `NOTICE` messages which fit in a line.
+///
+/// The server relays our messages with our full `nick!user@host` prefix, so it
+/// counts toward the 512 bytes limit. When the username or hostname is not known,
+/// the longest common values are assumed. Tags are not counted, they have their
+/// own limit of 4094 bytes which is checked when splitting.
+///
+/// Text is split on spaces when possible, otherwise between grapheme clusters.
+/// Formatting carries over from a message to the next, and newlines always
+/// start a new message.
+#[derive(Clone)]
+pub struct Splitter {
+    prefix: PrefixUser,
+    encode: EncodePolicy,
+    tags: Vec<Tag>,
+}
+
+impl Splitter {
+
+    /// Create a splitter for messages sent with the given prefix, in UTF-8.
+    pub fn new(prefix: PrefixUser) -> Splitter {
+        Splitter {
+            prefix,
+            encode: EncodePolicy::default(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Set the encoding policy used to send the messages.
+    pub fn encoding(mut self, encode: EncodePolicy) -> Splitter {
+        self.encode = encode;
+        self
+    }
+
+    /// Add a tag to every message.
+    pub fn tag(mut self, tag: Tag) -> Splitter {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Split the text into `PRIVMSG` messages.
+    pub fn privmsg(&self, target: &str, text: &str) -> Result<Vec<Message>, BuildError> {
+        self.split(Code::Privmsg, target, text)
+    }
+
+    /// Split the text into `NOTICE` messages.
+    pub fn notice(&self, target: &str, text: &str) -> Result<Vec<Message>, BuildError> {
+        self.split(Code::Notice, target, text)
+    }
+
+    /// Split the text into messages with the given code.
+    ///
+    /// Empty lines are skipped. If the target is so long that nothing fits,
+    /// each message holds a single grapheme cluster.
+    ///
+    /// `BuildError::TagsTooLong` is returned if the tags take more than 4094 bytes.
+    pub fn split(&self, code: Code, target: &str, text: &str) -> Result<Vec<Message>, BuildError> {
+        let tags_len = self.tags_len();
+        if tags_len > MAX_CLIENT_TAGS_LENGTH {
+            return Err(BuildError::TagsTooLong(tags_len));
+        }
+
+        let budget = MAX_LINE_LENGTH.saturating_sub(self.overhead(&code, target));
+        let mut messages = Vec::new();
+        for line in text.split('\n') {
+            let line = line.trim_end_matches('\r');
+            for part in self.split_line(line, budget) {
+                messages.push(Message {
+                    tags: self.tags.clone(),
+                    prefix: None,
+                    code: code.clone(),
+                    args: vec![target.into(), part],
+                    trailing: true,
+                });
+            }
+        }
+        Ok(messages)
+    }
+
+    // Length of the tag section, with the '@' and the space.
+    fn tags_len(&self) -> usize {
+        if self.tags.is_empty() {
+            return 0;
+        }
+        let tags: Vec<String> = self.tags.iter().map(|tag| tag.to_string()).collect();
+        1 + tags.join(";").len() + 1
+    }
+
+    fn len(&self, text: &str) -> usize {
+        self.encode.encode(text).map_or(text.len(), |bytes| bytes.len())
+    }
+
+    // Bytes used by everything but the text, in ":prefix CODE target :text\r\n".
+    fn overhead(&self, code: &Code, target: &str) -> usize {
+        let mut len = 1 + self.len(&self.prefix.nickname);
+        len += match self.prefix.username {
+            Some(ref username) => 1 + self.len(username),
+            None => USERNAME_RESERVE,
+        };
+        len += match self.prefix.hostname {
+            Some(ref hostname) => 1 + self.len(hostname),
+            None => HOSTNAME_RESERVE,
+        };
+        len + 1 + code.to_string().len() + 1 + self.len(target) + 2 + 2
+    }
+
+    // Split a single line of formatted text, each part fitting in the budget once encoded.
+    fn split_line(&self, line: &str, budget: usize) -> Vec<String> {
+        // Styled grapheme clusters with their encoded length.
+        let mut units: Vec<(Style, &str, usize)> = Vec::new();
+        let parsed = FormattedText::parse(line);
+        for span in parsed.spans.iter() {
+            for grapheme in span.text.graphemes(true) {
+                units.push((span.style, grapheme, self.len(grapheme)));
+            }
+        }
+
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < units.len() {
+            let mut style = Style::default();
+            let mut len = 0;
+            let mut end = start;
+            // Position of the last space, to break the line there.
+            let mut space = None;
+
+            while end < units.len() {
+                let (next, grapheme, grapheme_len) = units[end];
+                let mut cost = grapheme_len;
+                if next != style {
+                    let mut codes = String::new();
+                    // Writing to a String cannot fail.
+                    let _ = style.write_span_start(&next, grapheme, &mut codes);
+                    cost += self.len(&codes);
+                }
+                if len + cost > budget && end > start {
+                    break;
+                }
+                if grapheme == " " && end > start {
+                    space = Some(end);
+                }
+                len += cost;
+                style = next;
+                end += 1;
+            }
+
+            // Break on the last space, which is dropped, unless the whole line fits.
+            let (stop, resume) = match space {
+                _ if end < units.len() && units[end].1 == " " => (end, end + 1),
+                Some(space) if end < units.len() => (space, space + 1),
+                _ => (end, end),
+            };
+            let mut part = FormattedText::new();
+            for &(style, grapheme, _) in &units[start..stop] {
+                part = part.push(style, grapheme);
+            }
+            parts.push(part.to_string());
+            start = resume;
+        }
+        parts
+    }
+
+}
+
+#[cfg(test)]
+fn relayed_len(prefix: &PrefixUser, msg: &Message) -> usize {
+    format!(":{} {}\r\n", prefix, msg).len()
+}
+
+#[test]
+fn test_short() {
+    let splitter = Splitter::new(PrefixUser::new("bot", "bot", "example.com"));
+    let messages = splitter.privmsg("#chan", "hello world\nsecond line\n\n").unwrap();
+    let lines: Vec<String> = messages.iter().map(|msg| msg.to_string()).collect();
+    assert_eq!(lines, vec!["PRIVMSG #chan :hello world", "PRIVMSG #chan :second line"]);
+}
+
+#[test]
+fn test_word_boundaries() {
+    let prefix = PrefixUser::new("bot", "bot", "example.com");
+    let splitter = Splitter::new(prefix.clone());
+    let text = "lorem ipsum dolor sit amet ".repeat(50);
+    let messages = splitter.notice("#chan", text.trim()).unwrap();
+    assert!(messages.len() > 1);
+    let mut joined = Vec::new();
+    for (idx, msg) in messages.iter().enumerate() {
+        let len = relayed_len(&prefix, msg);
+        assert!(len <= 512);
+        assert!(len > 480 || idx == messages.len() - 1);
+        assert!(!msg.args[1].starts_with(' ') && !msg.args[1].ends_with(' '));
+        joined.push(msg.args[1].clone());
+    }
+    assert_eq!(joined.join(" "), text.trim());
+}
+
+#[test]
+fn test_graphemes() {
+    let prefix = PrefixUser::new("bot", "bot", "example.com");
+    let splitter = Splitter::new(prefix.clone());
+    // "e" with a combining acute accent, without any space.
+    let text = "e\u{301}".repeat(400);
+    let messages = splitter.privmsg("#chan", &text).unwrap();
+    for msg in messages.iter() {
+        assert!(relayed_len(&prefix, msg) <= 512);
+        assert!(msg.args[1].starts_with('e') && msg.args[1].ends_with('\u{301}'));
+    }
+    let joined: String = messages.iter().map(|msg| &msg.args[1][..]).collect();
+    assert_eq!(joined, text);
+}
+
+#[test]
+fn test_unknown_prefix() {
+    let prefix = PrefixUser {
+        nickname: "bot".into(),
+        username: None,
+        hostname: None,
+    };
+    let splitter = Splitter::new(prefix);
+    let messages = splitter.privmsg("#chan", &"a".repeat(1000)).unwrap();
+    let worst = PrefixUser::new("bot", &"u".repeat(10), &"h".repeat(63));
+    for msg in messages.iter() {
+        assert!(relayed_len(&worst, msg) <= 512);
+    }
+}
+
+#[test]
+fn test_formatting_carries_over() {
+    let prefix = PrefixUser::new("bot", "bot", "example.com");
+    let splitter = Splitter::new(prefix);
+    let text = format!("\x02\x0304{}", "word ".repeat(200));
+    let messages = splitter.privmsg("#chan", text.trim()).unwrap();
+    assert!(messages.len() > 1);
+    for msg in messages.iter() {
+        assert!(msg.args[1].starts_with("\x02\x0304word"));
+    }
+}
+
+#[test]
+fn test_encoding() {
+    use encoding::all::ISO_8859_1;
+
+    let prefix = PrefixUser::new("bot", "bot", "example.com");
+    let utf8 = Splitter::new(prefix.clone()).privmsg("#chan", &"é".repeat(600)).unwrap();
+    let latin1 = Splitter::new(prefix).encoding(EncodePolicy::new(ISO_8859_1)).privmsg("#chan", &"é".repeat(600)).unwrap();
+    assert_eq!(utf8.len(), 3);
+    assert_eq!(latin1.len(), 2);
+}
+
+#[test]
+fn test_tags_too_long() {
+    let prefix = PrefixUser::new("bot", "bot", "example.com");
+    let splitter = Splitter::new(prefix).tag(Tag::new("+draft/reply", Some("a".repeat(4000))));
+    let messages = splitter.privmsg("#chan", "hello").unwrap();
+    assert_eq!(messages[0].tags.len(), 1);
+
+    let splitter = splitter.tag(Tag::new("+draft/label", Some("b".repeat(100))));
+    assert_eq!(splitter.privmsg("#chan", "hello"), Err(BuildError::TagsTooLong(4129)));
+}
@@ -0,0 +1,407 @@
+use std::convert::TryFrom;
+use std::io::{self, BufRead, BufReader, Cursor, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use ring::digest::{self, SHA256};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{self, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::pki_types::pem::PemObject;
+
+use code::Code;
+use message::Message;
+use transport::TransportWriter;
+
+/// How TLS connections are made, available with the `tls` feature.
+///
+/// Servers are verified with the Mozilla root certificates by default, and
+/// more roots can be added for private networks. Self-signed servers can be
+/// trusted by pinning the SHA-256 hash of their public key instead.
+///
+/// A client certificate can be given, for CertFP authentication.
+///
+/// With `starttls`, the connection starts in plain text and is upgraded by
+/// sending `STARTTLS` before registration. The lines received before the
+/// upgrade, `RPL_STARTTLS` included, are given as usual.
+#[derive(Clone)]
+pub struct TlsSettings {
+    default_roots: bool,
+    roots: Vec<CertificateDer<'static>>,
+    pins: Vec<[u8; 32]>,
+    client_cert: Option<(Vec<CertificateDer<'static>>, Arc<PrivateKeyDer<'static>>)>,
+    server_name: Option<String>,
+    starttls: bool,
+}
+
+/// The Mozilla root certificates, no pins, no client certificate, and direct TLS.
+impl Default for TlsSettings {
+
+    fn default() -> TlsSettings {
+        TlsSettings::new()
+    }
+
+}
+
+impl TlsSettings {
+
+    /// Create settings verifying servers with the Mozilla root certificates.
+    pub fn new() -> TlsSettings {
+        TlsSettings {
+            default_roots: true,
+            roots: Vec::new(),
+            pins: Vec::new(),
+            client_cert: None,
+            server_name: None,
+            starttls: false,
+        }
+    }
+
+    /// Do not trust the Mozilla root certificates, only the ones added.
+    pub fn without_default_roots(mut self) -> TlsSettings {
+        self.default_roots = false;
+        self
+    }
+
+    /// Trust a root certificate, in DER.
+    pub fn add_root_certificate(mut self, der: Vec<u8>) -> TlsSettings {
+        self.roots.push(CertificateDer::from(der));
+        self
+    }
+
+    /// Trust every root certificate of a PEM file.
+    pub fn add_root_pem(mut self, pem: &[u8]) -> io::Result<TlsSettings> {
+        for cert in CertificateDer::pem_slice_iter(pem) {
+            self.roots.push(cert.map_err(invalid_data)?);
+        }
+        Ok(self)
+    }
+
+    /// Pin the SHA-256 hash of the server's public key, its `SubjectPublicKeyInfo`.
+    ///
+    /// Once a key is pinned, the certificate chain and the server name are not
+    /// verified anymore: servers are trusted if and only if their key matches
+    /// one of the pins. Use `spki_sha256` to compute the hash of a certificate.
+    pub fn pin_spki_sha256(mut self, hash: [u8; 32]) -> TlsSettings {
+        self.pins.push(hash);
+        self
+    }
+
+    /// Authenticate with a client certificate, such as for CertFP.
+    ///
+    /// The chain and the key are in DER. The key can be PKCS#8, PKCS#1 or SEC1.
+    pub fn client_certificate(mut self, chain: Vec<Vec<u8>>, key: Vec<u8>) -> io::Result<TlsSettings> {
+        let key = PrivateKeyDer::try_from(key).map_err(invalid_data)?;
+        let chain = chain.into_iter().map(CertificateDer::from).collect();
+        self.client_cert = Some((chain, Arc::new(key)));
+        Ok(self)
+    }
+
+    /// Authenticate with a client certificate, from a PEM file holding the chain and the key.
+    pub fn client_certificate_pem(mut self, pem: &[u8]) -> io::Result<TlsSettings> {
+        let chain = CertificateDer::pem_slice_iter(pem).collect::<Result<Vec<_>, _>>().map_err(invalid_data)?;
+        let key = PrivateKeyDer::from_pem_slice(pem).map_err(invalid_data)?;
+        self.client_cert = Some((chain, Arc::new(key)));
+        Ok(self)
+    }
+
+    /// Verify the server with this name, instead of the host of the address.
+    pub fn server_name<S: Into<String>>(mut self, name: S) -> TlsSettings {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    /// Connect in plain text and upgrade the connection with `STARTTLS`.
+    pub fn starttls(mut self) -> TlsSettings {
+        self.starttls = true;
+        self
+    }
+
+    /// Compute the SHA-256 hash of the public key of a certificate in DER, for `pin_spki_sha256`.
+    ///
+    /// `None` is returned if the certificate is malformed.
+    pub fn spki_sha256(certificate: &[u8]) -> Option<[u8; 32]> {
+        let spki = spki(certificate)?;
+        let mut hash = [0; 32];
+        hash.copy_from_slice(digest::digest(&SHA256, spki).as_ref());
+        Some(hash)
+    }
+
+    pub(crate) fn connector(&self) -> io::Result<TlsConnector> {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(invalid_data)?;
+
+        let builder = if self.pins.is_empty() {
+            let mut store = RootCertStore::empty();
+            if self.default_roots {
+                store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            }
+            for cert in self.roots.iter() {
+                store.add(cert.clone()).map_err(invalid_data)?;
+            }
+            builder.with_root_certificates(store)
+        } else {
+            builder.dangerous().with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+                pins: self.pins.clone(),
+                provider,
+            }))
+        };
+
+        let config = match self.client_cert {
+            Some((ref chain, ref key)) => {
+                builder.with_client_auth_cert(chain.clone(), key.clone_key()).map_err(invalid_data)?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        Ok(TlsConnector {
+            config: Arc::new(config),
+            server_name: self.server_name.clone(),
+            starttls: self.starttls,
+        })
+    }
+
+}
+
+fn invalid_data<E: Into<Box<dyn (::std::error::Error) + Send + Sync>>>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+// Trusts servers whose public key is pinned, whatever signed their certificate.
+#[derive(Debug)]
+struct PinnedVerifier {
+    pins: Vec<[u8; 32]>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, _: &[CertificateDer<'_>], _: &ServerName<'_>,
+                          _: &[u8], _: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        match TlsSettings::spki_sha256(end_entity) {
+            Some(hash) if self.pins.contains(&hash) => Ok(ServerCertVerified::assertion()),
+            Some(_) => Err(rustls::Error::InvalidCertificate(rustls::CertificateError::ApplicationVerificationFailure)),
+            None => Err(rustls::Error::InvalidCertificate(rustls::CertificateError::BadEncoding)),
+        }
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct)
+                              -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct)
+                              -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+
+}
+
+// Split a DER element into its tag, its content and what follows it.
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)? as usize;
+    let (len, header) = if first < 0x80 {
+        (first, 2)
+    } else {
+        // Long form, the low bits give the amount of length bytes.
+        let count = first & 0x7f;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let bytes = data.get(2..2 + count)?;
+        (bytes.iter().fold(0, |len, &byte| len << 8 | byte as usize), 2 + count)
+    };
+    let end = header.checked_add(len)?;
+    if end > data.len() {
+        return None;
+    }
+    Some((tag, &data[header..end], &data[end..]))
+}
+
+// Find the SubjectPublicKeyInfo of a certificate.
+fn spki(certificate: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(certificate)?;
+    let (_, tbs, _) = der_element(certificate)?;
+    let (tag, _, mut rest) = der_element(tbs)?;
+    // The version is optional and explicitly tagged, the serial number is always there.
+    if tag != 0xa0 {
+        rest = tbs;
+    }
+    // Skip the serial number, signature algorithm, issuer, validity and subject.
+    for _ in 0..5 {
+        rest = der_element(rest)?.2;
+    }
+    let (_, _, after) = der_element(rest)?;
+    Some(&rest[..rest.len() - after.len()])
+}
+
+#[derive(Clone)]
+pub(crate) struct TlsConnector {
+    config: Arc<ClientConfig>,
+    server_name: Option<String>,
+    starttls: bool,
+}
+
+impl TlsConnector {
+
+    // Do the handshake on the connection to the address, after the STARTTLS upgrade if enabled.
+    pub(crate) fn open(&self, mut sock: TcpStream, address: &str) -> io::Result<(Box<dyn Read + Send>, TlsWriter)> {
+        let early = if self.starttls {
+            starttls(&sock)?
+        } else {
+            Vec::new()
+        };
+
+        let name = match self.server_name {
+            Some(ref name) => name.clone(),
+            None => host(address).into(),
+        };
+        let name = ServerName::try_from(name).map_err(invalid_data)?;
+        let mut conn = ClientConnection::new(self.config.clone(), name).map_err(invalid_data)?;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut sock)?;
+        }
+
+        let conn = Arc::new(Mutex::new(conn));
+        let reader = TlsReader {
+            conn: conn.clone(),
+            sock: sock.try_clone()?,
+        };
+        let writer = TlsWriter {
+            conn,
+            sock,
+        };
+        Ok((Box::new(Cursor::new(early).chain(reader)), writer))
+    }
+
+}
+
+// Host part of an address, without the port and the brackets of IPv6 addresses.
+fn host(address: &str) -> &str {
+    let host = match address.rfind(':') {
+        Some(idx) if !address[idx..].contains(']') => &address[..idx],
+        _ => address,
+    };
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+// Ask for the upgrade, returning the lines received until RPL_STARTTLS.
+fn starttls(mut sock: &TcpStream) -> io::Result<Vec<u8>> {
+    sock.write_all(b"STARTTLS\r\n")?;
+    // The server waits for the handshake after RPL_STARTTLS, so nothing is read too far.
+    let mut reader = BufReader::new(sock);
+    let mut early = Vec::new();
+    loop {
+        let start = early.len();
+        if reader.read_until(b'\n', &mut early)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed before STARTTLS"));
+        }
+        let line = String::from_utf8_lossy(&early[start..]).into_owned();
+        match Message::parse(&line).map(|msg| msg.code) {
+            Ok(Code::RplStarttls) => return Ok(early),
+            Ok(Code::ErrStarttls) | Ok(Code::ErrUnknowncommand) => {
+                return Err(io::Error::other(format!("STARTTLS refused: {}", line.trim_end())));
+            }
+            _ => {}
+        }
+    }
+}
+
+// Receiving half of a TLS connection.
+struct TlsReader {
+    conn: Arc<Mutex<ClientConnection>>,
+    sock: TcpStream,
+}
+
+impl Read for TlsReader {
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut raw = [0; 4096];
+        loop {
+            match self.conn.lock().unwrap().reader().read(buf) {
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                res => return res,
+            }
+
+            // Wait for data without holding the lock, so that the writer is not blocked.
+            let len = self.sock.read(&mut raw)?;
+            if len == 0 {
+                return Ok(0);
+            }
+
+            let mut conn = self.conn.lock().unwrap();
+            let mut data = &raw[..len];
+            while !data.is_empty() {
+                conn.read_tls(&mut data)?;
+                conn.process_new_packets().map_err(invalid_data)?;
+            }
+            // Answer the server, for key updates and such.
+            while conn.wants_write() {
+                conn.write_tls(&mut self.sock)?;
+            }
+        }
+    }
+
+}
+
+// Sending half of a TLS connection.
+pub(crate) struct TlsWriter {
+    conn: Arc<Mutex<ClientConnection>>,
+    sock: TcpStream,
+}
+
+impl TransportWriter for TlsWriter {
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        conn.send_close_notify();
+        while conn.wants_write() {
+            if conn.write_tls(&mut self.sock).is_err() {
+                break;
+            }
+        }
+        self.sock.shutdown(Shutdown::Both)
+    }
+
+}
+
+impl Write for TlsWriter {
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        conn.writer().write_all(buf)?;
+        while conn.wants_write() {
+            conn.write_tls(&mut self.sock)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+}
+
+#[test]
+fn test_host() {
+    assert_eq!(host("irc.example.com:6697"), "irc.example.com");
+    assert_eq!(host("[::1]:6697"), "::1");
+    assert_eq!(host("irc.example.com"), "irc.example.com");
+}
+
+#[test]
+fn test_spki_sha256() {
+    let key = rcgen::KeyPair::generate().unwrap();
+    let cert = rcgen::CertificateParams::new(vec!["localhost".into()]).unwrap().self_signed(&key).unwrap();
+    let expected = digest::digest(&SHA256, &key.public_key_der());
+    assert_eq!(TlsSettings::spki_sha256(cert.der()).map(|hash| hash.to_vec()), Some(expected.as_ref().to_vec()));
+    assert_eq!(TlsSettings::spki_sha256(&cert.der()[..40]), None);
+}
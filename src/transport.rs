@@ -0,0 +1,339 @@
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use proxy::Proxy;
+#[cfg(feature = "tls")]
+use tls::{TlsConnector, TlsSettings};
+
+/// Sending half of a connection made by a `Transport`.
+pub trait TransportWriter: Write + Send {
+
+    /// Shut the connection down.
+    ///
+    /// Reads on the receiving half must stop blocking and return an error or
+    /// an end of file, so that the `Writer` can disconnect and close the connection.
+    fn shutdown(&mut self) -> io::Result<()>;
+
+}
+
+impl TransportWriter for TcpStream {
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+
+}
+
+#[cfg(unix)]
+impl TransportWriter for UnixStream {
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        UnixStream::shutdown(self, Shutdown::Both)
+    }
+
+}
+
+/// Both halves of a connection, the receiving one and the sending one.
+pub type Connection = (Box<dyn Read + Send>, Box<dyn TransportWriter>);
+
+/// Makes the connection, the first time and on every reconnection.
+///
+/// It is implemented by closures returning a `Connection`, so any stream can be
+/// used, such as Unix domain sockets or in-memory pipes.
+pub trait Transport: Send + 'static {
+
+    /// Open a new connection.
+    fn connect(&mut self) -> io::Result<Connection>;
+
+}
+
+impl<F> Transport for F where F: FnMut() -> io::Result<Connection> + Send + 'static {
+
+    fn connect(&mut self) -> io::Result<Connection> {
+        self()
+    }
+
+}
+
+// Time given to a connection attempt to an address.
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Delay before starting the next attempt while the previous ones are pending, from RFC 8305.
+const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+// Connect to the first address which answers, Happy Eyeballs style.
+//
+// Addresses are tried with IPv6 and IPv4 interleaved. A new attempt is started when the
+// previous one fails, or when it did not succeed after a short delay. Each attempt gives
+// up after the timeout.
+pub(crate) fn connect_timeout<A: ToSocketAddrs>(address: A, timeout: Duration) -> io::Result<TcpStream> {
+    let addrs = interleave(address.to_socket_addrs()?.collect());
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = 0;
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "address did not resolve");
+
+    let attempt = |addr: SocketAddr| {
+        let sender = sender.clone();
+        // A late connection is dropped with the receiver.
+        thread::spawn(move || {
+            let _ = sender.send(TcpStream::connect_timeout(&addr, timeout));
+        });
+    };
+
+    for addr in addrs {
+        attempt(addr);
+        pending += 1;
+        match receiver.recv_timeout(ATTEMPT_DELAY) {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(err)) => {
+                pending -= 1;
+                last_err = err;
+            }
+            Err(_) => {}
+        }
+    }
+    while pending > 0 {
+        match receiver.recv() {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(err)) => {
+                pending -= 1;
+                last_err = err;
+            }
+            Err(_) => break,
+        }
+    }
+    Err(last_err)
+}
+
+// Bound the reads and writes of the handshakes made once connected, or remove the bound.
+pub(crate) fn set_timeouts(stream: &TcpStream, timeout: Option<Duration>) -> io::Result<()> {
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)
+}
+
+// Alternate between families, starting with the family of the first address.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_v6 = addrs.first().is_some_and(|addr| addr.is_ipv6());
+    let (mut first, mut second): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|addr| addr.is_ipv6() == first_v6);
+    let mut res = Vec::with_capacity(first.len() + second.len());
+    first.reverse();
+    second.reverse();
+    while !first.is_empty() || !second.is_empty() {
+        res.extend(first.pop());
+        res.extend(second.pop());
+    }
+    res
+}
+
+/// Connects to an address with TCP, through a proxy if any, and TLS with the `tls` feature.
+///
+/// Every address of the host is tried, IPv6 and IPv4 interleaved. A new attempt
+/// starts when the previous one failed or is slow to answer, and each one gives
+/// up after the timeout, 30 seconds by default. The proxy and TLS handshakes also
+/// fail when the other side does not answer within the timeout.
+#[derive(Clone)]
+pub struct TcpTransport {
+    address: String,
+    timeout: Duration,
+    proxy: Option<Proxy>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConnector>,
+}
+
+impl TcpTransport {
+
+    /// Create a transport to the given address, such as `irc.example.com:6667`.
+    pub fn new<A: AsRef<str>>(address: A) -> TcpTransport {
+        TcpTransport {
+            address: address.as_ref().into(),
+            timeout: DEFAULT_TIMEOUT,
+            proxy: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Set the time given to a connection attempt to an address, and to each read
+    /// and write of the handshakes.
+    pub fn timeout(mut self, timeout: Duration) -> TcpTransport {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Connect through the proxy, on every reconnection as well.
+    pub fn proxy(mut self, proxy: Proxy) -> TcpTransport {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Use TLS with the given settings, available with the `tls` feature.
+    ///
+    /// An error is returned if the certificates or the key are invalid.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: &TlsSettings) -> io::Result<TcpTransport> {
+        self.tls = Some(tls.connector()?);
+        Ok(self)
+    }
+
+}
+
+impl Transport for TcpTransport {
+
+    fn connect(&mut self) -> io::Result<Connection> {
+        let stream = match self.proxy {
+            Some(ref proxy) => proxy.connect_timeout(&self.address, self.timeout)?,
+            None => connect_timeout(&self.address[..], self.timeout)?,
+        };
+        #[cfg(feature = "tls")]
+        {
+            if let Some(ref tls) = self.tls {
+                let sock = stream.try_clone()?;
+                set_timeouts(&sock, Some(self.timeout))?;
+                let (reader, writer) = tls.open(stream, &self.address)?;
+                set_timeouts(&sock, None)?;
+                return Ok((reader, Box::new(writer)));
+            }
+        }
+        Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+    }
+
+}
+
+#[cfg(unix)]
+#[test]
+fn test_closure_transport() {
+    use std::io::{BufRead, BufReader};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use backoff::Backoff;
+    use charset::{DecodePolicy, EncodePolicy};
+    use connection::{connect_transport, Event, ReconnectionSettings};
+
+    // Each connection is a pair of sockets, the server's end is given to the test.
+    let (server_sender, servers) = mpsc::channel();
+    let transport = move || -> io::Result<Connection> {
+        let (client, server) = UnixStream::pair()?;
+        server_sender.send(server).unwrap();
+        Ok((Box::new(client.try_clone()?), Box::new(client)))
+    };
+    let reco = ReconnectionSettings::Reconnect {
+        max_attempts: 1,
+        delay_between_attempts: Duration::from_millis(0),
+        delay_after_disconnect: Duration::from_millis(0),
+        backoff: Backoff::Constant,
+        reset_after: Duration::from_secs(5 * 60),
+    };
+    let (writer, reader) = connect_transport(transport, reco, DecodePolicy::default(), EncodePolicy::default()).unwrap();
+
+    let mut server = servers.recv().unwrap();
+    server.write_all(b":server 001 bob :Welcome\r\n").unwrap();
+    match reader.recv().unwrap() {
+        Event::Message(msg) => assert_eq!(msg.args[1], "Welcome"),
+        event => panic!("unexpected event {:?}", event),
+    }
+    writer.raw("PING :server\r\n").unwrap();
+    let mut line = String::new();
+    BufReader::new(&server).read_line(&mut line).unwrap();
+    assert_eq!(line, "PING :server\r\n");
+
+    // Disconnecting shuts the socket down, and a new pair is made.
+    writer.disconnect().unwrap();
+    let events: Vec<Event> = reader.iter().take(3).collect();
+    assert!(matches!(events[..], [Event::Disconnected, Event::Reconnecting, Event::Reconnected]));
+    let _server = servers.recv_timeout(Duration::from_secs(5)).unwrap();
+    writer.close().unwrap();
+    assert!(matches!(reader.recv().unwrap(), Event::Closed(_)));
+}
+
+#[test]
+fn test_proxy_on_reconnect() {
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use backoff::Backoff;
+    use charset::{DecodePolicy, EncodePolicy};
+    use connection::{connect_transport, Event, ReconnectionSettings};
+
+    // SOCKS5 stand-in, which is also the server. It tells the target of every connection.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy = Proxy::socks5(listener.local_addr().unwrap().to_string());
+    let (target_sender, targets) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(2) {
+            let mut stream = stream.unwrap();
+            let mut buf = [0; 5];
+            stream.read_exact(&mut buf[..3]).unwrap();
+            stream.write_all(&[5, 0]).unwrap();
+            stream.read_exact(&mut buf).unwrap();
+            let mut target = vec![0; buf[4] as usize + 2];
+            stream.read_exact(&mut target).unwrap();
+            stream.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).unwrap();
+            target_sender.send((String::from_utf8_lossy(&target[..target.len() - 2]).into_owned(), stream)).unwrap();
+        }
+    });
+
+    let transport = TcpTransport::new("irc.example.onion:6667").proxy(proxy);
+    let reco = ReconnectionSettings::Reconnect {
+        max_attempts: 1,
+        delay_between_attempts: Duration::from_millis(0),
+        delay_after_disconnect: Duration::from_millis(0),
+        backoff: Backoff::Constant,
+        reset_after: Duration::from_secs(5 * 60),
+    };
+    let (writer, reader) = connect_transport(transport, reco, DecodePolicy::default(), EncodePolicy::default()).unwrap();
+    let (target, _first) = targets.recv().unwrap();
+    assert_eq!(target, "irc.example.onion");
+    writer.disconnect().unwrap();
+    let events: Vec<Event> = reader.iter().take(3).collect();
+    assert!(matches!(events[..], [Event::Disconnected, Event::Reconnecting, Event::Reconnected]));
+    let (target, _second) = targets.recv().unwrap();
+    assert_eq!(target, "irc.example.onion");
+    writer.close().unwrap();
+}
+
+#[test]
+fn test_interleave() {
+    let addrs: Vec<SocketAddr> = ["[::1]:1", "[::2]:1", "[::3]:1", "127.0.0.1:1", "127.0.0.2:1"].iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+    let ordered: Vec<String> = interleave(addrs).iter().map(|addr| addr.to_string()).collect();
+    assert_eq!(ordered, vec!["[::1]:1", "127.0.0.1:1", "[::2]:1", "127.0.0.2:1", "[::3]:1"]);
+}
+
+#[test]
+fn test_connect_timeout() {
+    use std::net::TcpListener;
+
+    // Whichever family localhost resolves to first, the listening one is found.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let stream = connect_timeout(("localhost", port), Duration::from_secs(5)).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), listener.local_addr().unwrap());
+    drop(listener);
+    assert!(connect_timeout(("localhost", port), Duration::from_secs(5)).is_err());
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_handshake_timeout() {
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    // The server accepts the connection but never answers the handshake.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut transport = TcpTransport::new(listener.local_addr().unwrap().to_string())
+        .timeout(Duration::from_millis(200))
+        .tls(&TlsSettings::new().server_name("irc.example.com"))
+        .unwrap();
+    let start = Instant::now();
+    assert!(transport.connect().is_err());
+    assert!(start.elapsed() < Duration::from_secs(2));
+}